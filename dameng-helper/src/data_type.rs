@@ -2,7 +2,7 @@ use crate::error::DmError;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum DmDateType {
     /// `NUMERIC 数据类型用于存储零、正负定点数。其中:精度是一个无符号整数，
     /// 定义 了总的数字数，精度范围是 1至38，标度定义了小数点右边的数字位数，定义时如省略 精度，则默认是 16。
@@ -83,15 +83,10 @@ pub enum DmDateType {
     // TODO 时间间隔数据类型
 
     //未知类型
+    #[default]
     Unknown,
 }
 
-impl Default for DmDateType {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
 impl FromStr for DmDateType {
     type Err = DmError;
 
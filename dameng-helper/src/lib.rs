@@ -96,7 +96,6 @@ impl DmAdapter for CursorImpl<StatementImpl<'_>> {
                 let num_cols = batch.num_cols();
                 let row_data: Vec<String> = (0..num_cols)
                     .map(|col_index| (col_index, batch.at(col_index, row_index).unwrap_or(&[])))
-                    .into_iter()
                     .map(|(col_index, x)| (col_index, String::from_utf8_lossy(x)))
                     .map(|(col_index, x)| case_sensitive_fn(col_index, x))
                     .collect();
@@ -108,6 +107,8 @@ impl DmAdapter for CursorImpl<StatementImpl<'_>> {
 }
 
 #[cfg(test)]
+// `test_dameng_table_desc` rewrites the rows in a `map`
+#[allow(clippy::manual_inspect)]
 mod tests {
     const DAMENG_CONNECTION: &str = "Driver={DM8};Server=0.0.0.0;UID=SYSDBA;PWD=SYSDBA001;";
 
@@ -1,4 +1,6 @@
+use crate::extension::odbc::OdbcColumnType;
 use crate::odbc_api;
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -9,4 +11,223 @@ pub enum OdbcHelperError {
     SqlParamsError(String),
     #[error("Failed to convert byte to {0}")]
     TypeConversionError(String),
+    #[error("value truncated, buffer too small for column(s):`{0}`")]
+    TruncatedError(String),
+    #[error("cell of column `{column}` is {size} bytes, larger than max_cell_bytes")]
+    CellTooLarge { column: String, size: usize },
+    #[error("result set schemas don't match:{0}")]
+    SchemaMismatchError(String),
+    #[error("invalid row:{0}")]
+    RowValidationError(String),
+    #[error("column `{0}` not found")]
+    ColumnNotFound(String),
+    #[error("invalid date `{year:04}-{month:02}-{day:02}`, supported years are -9999 to 9999")]
+    InvalidDate { year: i16, month: u16, day: u16 },
+    #[error("expected a single value, got {rows} rows of {columns} columns")]
+    NotScalar { rows: usize, columns: usize },
+    #[error("expected a single column, got {0} columns")]
+    NotSingleColumn(usize),
+    #[error("invalid timestamp, `{field}` {value} is out of range")]
+    InvalidTimestamp { field: &'static str, value: i64 },
+    #[error("column `{column}` value `{value}` is not an allowed ENUM/SET value")]
+    ValueNotAllowed { column: String, value: String },
+    #[error("invalid column name `{0}`, must not be empty")]
+    InvalidColumnName(String),
+    #[error("column `{column}` is described as {expected} but returned {got:?} cells")]
+    BufferKindMismatch {
+        column: String,
+        expected: String,
+        got: OdbcColumnType,
+    },
+    #[error("failed to convert column `{column}`: {source}")]
+    ColumnConversionError {
+        column: String,
+        #[source]
+        source: Box<OdbcHelperError>,
+    },
+}
+
+/// An ODBC diagnostic record with the message already decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Five characters, the first two are the class, e.g. `01` for warnings.
+    pub sqlstate: String,
+    /// Error code of the driver or driver manager.
+    pub native_code: i32,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn is_warning(&self) -> bool {
+        is_warning(&self.sqlstate)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[SQLSTATE:{}] {}", self.sqlstate, self.message)
+    }
+}
+
+impl From<&odbc_api::handles::Record> for Diagnostic {
+    fn from(record: &odbc_api::handles::Record) -> Self {
+        Diagnostic {
+            sqlstate: record.state.as_str().to_string(),
+            native_code: record.native_error,
+            message: odbc_api::handles::slice_to_cow_utf8(&record.message).to_string(),
+        }
+    }
+}
+
+impl OdbcHelperError {
+    /// The diagnostic record of an `OdbcError` raised by a failed ODBC call.
+    pub fn diagnostic(&self) -> Option<Diagnostic> {
+        match self {
+            OdbcHelperError::OdbcError(odbc_api::Error::Diagnostics { record, .. }) => {
+                Some(record.into())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Whether `sqlstate` is of class `01` (e.g. `01004` string data right truncation), a warning
+/// the statement succeeded with rather than an error.
+pub fn is_warning(sqlstate: &str) -> bool {
+    sqlstate.starts_with("01")
+}
+
+/// Split diagnostics into `(errors, warnings)`, keeping their order, so callers can log the
+/// warnings and only fail on the errors.
+pub fn split_diagnostics(diagnostics: Vec<Diagnostic>) -> (Vec<Diagnostic>, Vec<Diagnostic>) {
+    diagnostics.into_iter().partition(|d| !d.is_warning())
+}
+
+/// Best-effort name of the constraint an integrity violation (SQLSTATE class `23`) names in its
+/// message, `None` for other diagnostics or unknown message formats. Recognized are e.g.
+/// - Postgres: `... violates unique constraint "users_email_key"`
+/// - SQL Server: `Violation of UNIQUE KEY constraint 'UQ_email'...`,
+///   `... with unique index 'IX_email'...`
+/// - MySQL: `Duplicate entry 'a@b.c' for key 'users.email'`,
+///   ``... CONSTRAINT `fk_user` FOREIGN KEY ...``; the `table.` prefix MySQL 8 puts before a key
+///   name is removed.
+pub fn parse_constraint_name(diag: &Diagnostic) -> Option<String> {
+    if !diag.sqlstate.starts_with("23") {
+        return None;
+    }
+    let lower = diag.message.to_ascii_lowercase();
+    ["constraint ", "unique index ", "for key "]
+        .iter()
+        .find_map(|marker| {
+            lower.match_indices(marker).find_map(|(index, _)| {
+                let name = quoted(&diag.message[index + marker.len()..])?;
+                Some(if *marker == "for key " {
+                    name.rsplit('.').next().unwrap_or(name)
+                } else {
+                    name
+                })
+            })
+        })
+        .map(|name| name.to_string())
+}
+
+/// The text between a leading `"`, `'` or `` ` `` and its closing quote.
+fn quoted(text: &str) -> Option<&str> {
+    let quote = text
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '"' | '\'' | '`'))?;
+    let rest = &text[1..];
+    rest.find(quote)
+        .map(|end| &rest[..end])
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::odbc_api::handles::{Record, SqlChar, State};
+
+    #[test]
+    fn test_split_diagnostics() {
+        assert!(is_warning("01004"));
+        assert!(!is_warning("42S02"));
+
+        let diagnostic = |sqlstate: &str| Diagnostic {
+            sqlstate: sqlstate.to_string(),
+            native_code: 0,
+            message: String::new(),
+        };
+        let (errors, warnings) = split_diagnostics(vec![
+            diagnostic("01000"),
+            diagnostic("HY000"),
+            diagnostic("01004"),
+        ]);
+        assert_eq!(errors, vec![diagnostic("HY000")]);
+        assert_eq!(warnings[1].sqlstate, "01004");
+    }
+
+    #[test]
+    fn test_diagnostic_from_record() {
+        let record = Record {
+            state: State(*b"42S02"),
+            native_error: 208,
+            message: b"Invalid object name 't'."
+                .iter()
+                .map(|b| SqlChar::from(*b))
+                .collect(),
+        };
+        let diagnostic = Diagnostic::from(&record);
+        assert_eq!(diagnostic.native_code, 208);
+        assert!(!diagnostic.is_warning());
+        assert_eq!(
+            diagnostic.to_string(),
+            "[SQLSTATE:42S02] Invalid object name 't'."
+        );
+    }
+
+    #[test]
+    fn test_parse_constraint_name() {
+        let diagnostic = |sqlstate: &str, message: &str| Diagnostic {
+            sqlstate: sqlstate.to_string(),
+            native_code: 0,
+            message: message.to_string(),
+        };
+        let cases = [
+            (
+                "ERROR: duplicate key value violates unique constraint \"users_email_key\"",
+                "users_email_key",
+            ),
+            (
+                "Violation of UNIQUE KEY constraint 'UQ_email'. Cannot insert duplicate key in \
+                 object 'dbo.users'.",
+                "UQ_email",
+            ),
+            (
+                "Cannot insert duplicate key row in object 'dbo.users' with unique index \
+                 'IX_email'.",
+                "IX_email",
+            ),
+            ("Duplicate entry 'a@b.c' for key 'users.email'", "email"),
+            (
+                "Cannot add or update a child row: a foreign key constraint fails (`db`.`orders`, \
+                 CONSTRAINT `fk_user` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`))",
+                "fk_user",
+            ),
+        ];
+        for (message, name) in cases {
+            assert_eq!(
+                parse_constraint_name(&diagnostic("23000", message)).as_deref(),
+                Some(name)
+            );
+        }
+        assert_eq!(
+            parse_constraint_name(&diagnostic("23505", "unique violation")),
+            None
+        );
+        assert_eq!(
+            parse_constraint_name(&diagnostic("42000", cases[0].0)),
+            None
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use crate::error::OdbcHelperError;
 use crate::executor::batch::BatchResult;
 use crate::executor::batch::Operation;
 use crate::executor::execute::ExecResult;
@@ -5,16 +6,21 @@ use crate::executor::query::QueryResult;
 use crate::executor::statement::StatementInput;
 use crate::executor::table::{TableDescArgsString, TableDescResult};
 use crate::executor::SupportDatabase;
-use crate::extension::odbc::{OdbcColumn, OdbcColumnItem};
+use crate::extension::odbc::{
+    bits_to_integer, check_buffer_kind, coerce_items, collapse_midnight_timestamps, declared_scale,
+    hex_text_to_binary, is_binary, is_interval, is_timestamp_tz, normalize_names,
+    normalize_tinyint, round_to_scale, strip_bom, to_bit, to_interval, to_timestamp_tz,
+    to_unsigned_bigint, try_convert_view, CoercionPolicy, ConversionReport, DataTypeKind,
+    OdbcColumn, OdbcColumnItem, OdbcColumnType, TextBufferPolicy, TinyIntSign,
+};
 use crate::odbc_api::{
     buffers::{AnySlice, BufferDesc, ColumnarAnyBuffer},
     handles::StatementImpl,
-    ColumnDescription, Connection, Cursor, CursorImpl, ParameterCollectionRef, ResultSetMetadata,
+    Connection, Cursor, CursorImpl, DataType, ParameterCollectionRef, ResultSetMetadata,
 };
-use crate::{Convert, TryConvert};
+use crate::TryConvert;
 use dameng_helper::DmAdapter;
 use either::Either;
-use std::ops::IndexMut;
 
 pub trait ConnectionTrait {
     /// Execute a `[Statement]`  INSERT,UPDATE,DELETE
@@ -52,9 +58,15 @@ pub struct OdbcDbConnection<'a> {
     pub options: Options,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Options {
     pub database: SupportDatabase,
+    // Fetch size: rows bound per buffer and fetched in one round-trip,default is 128.
+    // Larger values save round-trips on fast networks, smaller values bound the memory of
+    // wide rows: the buffer takes `max_batch_size` times the bytes per row, where every
+    // text/binary column reserves its column size capped at `max_str_len`/`max_binary_len`
+    // (see `fetch_buffer_bytes`).
+    // `max_cell_bytes` only caps the converted cells, it doesn't shrink the fetch buffer.
     pub max_batch_size: usize,
     pub max_str_len: usize,
     pub max_binary_len: usize,
@@ -62,10 +74,59 @@ pub struct Options {
     // false:all column name convert uppercase
     // true: ignore，keep original column name
     pub case_sensitive: bool,
+    // When a text/binary value is larger than its buffer the driver truncates it,
+    // reporting `SQL_NO_TOTAL` or the full length in the indicator.
+    // false: keep the truncated value,default is false.
+    // true: return `OdbcHelperError::TruncatedError`
+    pub error_for_truncation: bool,
+    // Upper bound for a single text/binary cell after conversion, protects against huge LOBs.
+    // 0: no limit,default is 0.
+    pub max_cell_bytes: usize,
+    // What to do with a cell larger than `max_cell_bytes`.
+    // false: truncate the value and log a warning,default is false.
+    // true: return `OdbcHelperError::CellTooLarge`
+    pub error_for_oversized_cell: bool,
+    // Target type of the converted cells per source type family, for every column,
+    // e.g. `DataTypeKind::Bit` => `OdbcColumnType::I32`. Empty by default.
+    pub coercion: CoercionPolicy,
+    // Round `F32`/`F64` cells to the scale declared by the column's `DataType`, e.g. a
+    // `DECIMAL(10,2)` coerced to `F64`, to drop binary float noise like `0.30000000000000004`.
+    // false: keep the raw values,default is false.
+    pub round_floats_to_scale: bool,
+    // Records the cells `round_floats_to_scale` changed by more than its threshold, see
+    // `ConversionReport`.
+    // None: don't record,default is None.
+    pub conversion_report: Option<ConversionReport>,
+    // Sign of `TINYINT` in the source database, drivers surface it as `I8` or `U8`.
+    // When set every `TINYINT` cell becomes `I16`, see `normalize_tinyint`.
+    // None: keep the driver's type,default is None.
+    pub tinyint_sign: Option<TinyIntSign>,
+    // Buffer kind bound for text columns, some drivers corrupt non-ASCII text when a
+    // narrow buffer is bound to a wide column (or the other way round).
+    // AlwaysWide: `WText` for every text column,default is AlwaysWide.
+    // ByDataType: `WText` for `WChar`/`WVarchar`, `Text` for `Char`/`Varchar`/`LongVarchar`.
+    pub text_buffers: TextBufferPolicy,
+    // Strip a leading UTF-8 BOM (`\u{FEFF}`) from `Text`/`WText` cells, some sources store it
+    // inside VARCHAR data where it breaks comparisons and JSON keys.
+    // false: keep the value as stored,default is false.
+    pub strip_bom: bool,
+    // Convert `Timestamp` cells at exactly midnight to `Date`, for schemas storing dates in
+    // datetime columns. Checked per cell, see `collapse_midnight_timestamps`.
+    // false: keep timestamps,default is false.
+    pub collapse_midnight_timestamps: bool,
+    // Decode text cells of binary columns as hex (`0xDEADBEEF` or `DEADBEEF`) into `Binary`,
+    // for drivers returning `VARBINARY` through the text buffer, see `hex_text_to_binary`.
+    // false: keep the text,default is false.
+    pub hex_binary_text: bool,
+    // Fail with `OdbcHelperError::BufferKindMismatch` when the driver returns a column in a
+    // buffer not matching its described `DataType`, e.g. `F64` cells of a `BIGINT` column,
+    // see `check_buffer_kind`.
+    // false: keep the cells as returned,default is false.
+    pub strict_buffer_kind: bool,
 }
 
 impl Options {
-    // Default Max Buffer Size 256
+    // Default fetch size 128 rows
     pub const MAX_BATCH_SIZE: usize = 1 << 7;
     // Default Max string length 1K
     pub const MAX_STR_LEN: usize = 1024;
@@ -79,6 +140,18 @@ impl Options {
             max_str_len: Self::MAX_STR_LEN,
             max_binary_len: Self::MAX_BINARY_LEN,
             case_sensitive: false,
+            error_for_truncation: false,
+            max_cell_bytes: 0,
+            error_for_oversized_cell: false,
+            coercion: CoercionPolicy::new(),
+            round_floats_to_scale: false,
+            conversion_report: None,
+            tinyint_sign: None,
+            text_buffers: TextBufferPolicy::default(),
+            strip_bom: false,
+            collapse_midnight_timestamps: false,
+            hex_binary_text: false,
+            strict_buffer_kind: false,
         }
     }
 
@@ -186,6 +259,237 @@ impl<'a> ConnectionTrait for OdbcDbConnection<'a> {
     }
 }
 
+/// Map `odbc_api::Error::TooLargeValueForBuffer` to `OdbcHelperError::TruncatedError`.
+///
+/// The driver only reports that some value in the batch was truncated, so the error names every
+/// text/binary column whose buffer is smaller than its declared size.
+fn truncation_error(
+    err: crate::odbc_api::Error,
+    columns: &[OdbcColumn],
+    descs: &[BufferDesc],
+) -> anyhow::Error {
+    if !matches!(err, crate::odbc_api::Error::TooLargeValueForBuffer) {
+        return err.into();
+    }
+    let names = columns
+        .iter()
+        .zip(descs.iter())
+        .filter(|(c, desc)| {
+            let declared = c.data_type.column_size();
+            match desc {
+                BufferDesc::Text { max_str_len } | BufferDesc::WText { max_str_len } => {
+                    declared == 0 || declared > *max_str_len
+                }
+                BufferDesc::Binary { length } => declared == 0 || declared > *length,
+                _ => false,
+            }
+        })
+        .map(|(c, _)| c.name.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+    OdbcHelperError::TruncatedError(names).into()
+}
+
+/// Enforce `Options::max_cell_bytes` on the converted cells of `column`.
+/// Text is cut on a UTF-8 character boundary so the kept prefix stays valid.
+fn limit_cell_size(
+    items: &mut [OdbcColumnItem],
+    column: &OdbcColumn,
+    options: &Options,
+) -> Result<(), OdbcHelperError> {
+    let limit = options.max_cell_bytes;
+    if limit == 0 {
+        return Ok(());
+    }
+    for item in items.iter_mut() {
+        let is_text = match item.odbc_type {
+            OdbcColumnType::Text | OdbcColumnType::WText => true,
+            OdbcColumnType::Binary => false,
+            _ => continue,
+        };
+        let value = match item.value.as_mut() {
+            Some(value) if value.len() > limit => value,
+            _ => continue,
+        };
+        if options.error_for_oversized_cell {
+            return Err(OdbcHelperError::CellTooLarge {
+                column: column.name.clone(),
+                size: value.len(),
+            });
+        }
+
+        let mut len = limit;
+        if is_text {
+            // step back over UTF-8 continuation bytes
+            while len > 0 && value[len] & 0xC0 == 0x80 {
+                len -= 1;
+            }
+        }
+        warn!(
+            "truncate cell of column `{}` from {} to {} bytes",
+            column.name,
+            value.len(),
+            len
+        );
+        value.truncate(len);
+    }
+    Ok(())
+}
+
+/// The columns of a result set, names normalized with `normalize_names`.
+pub fn cursor_columns<C: ResultSetMetadata>(cursor: &mut C) -> anyhow::Result<Vec<OdbcColumn>> {
+    let mut columns = vec![];
+    for index in 0..cursor.num_result_cols()?.try_into()? {
+        columns.push(OdbcColumn::from_cursor(cursor, index + 1)?);
+    }
+    normalize_names(&mut columns);
+    Ok(columns)
+}
+
+/// Bytes of the row buffer [`for_each_batch`] binds for `columns`, to tune `max_batch_size`.
+pub fn fetch_buffer_bytes(columns: &[OdbcColumn], options: &Options) -> anyhow::Result<usize> {
+    let bytes_per_row = columns
+        .iter()
+        .map(|c| <(&OdbcColumn, &Options) as TryConvert<BufferDesc>>::try_convert((c, options)))
+        .map(|desc| desc.map(|d| d.bytes_per_row()))
+        .sum::<Result<usize, _>>()
+        .map_err(|e| anyhow!(e))?;
+    Ok(bytes_per_row * options.max_batch_size)
+}
+
+/// The conversions applied to the cells of `column` after they are read from the buffer.
+///
+/// Cells of character columns (`CHAR`, `VARCHAR`, ...) stay text unless `options.coercion` has an
+/// explicit entry for their type, so keys like `"00123"` keep their leading zeros.
+fn post_process(
+    items: &mut [OdbcColumnItem],
+    column: &OdbcColumn,
+    options: &Options,
+    first_row: usize,
+) -> Result<(), OdbcHelperError> {
+    if options.strip_bom {
+        strip_bom(items);
+    }
+    if is_timestamp_tz(&column.data_type) {
+        to_timestamp_tz(items)?;
+    }
+    if is_interval(&column.data_type) {
+        to_interval(items)?;
+    }
+    if column.is_unsigned_bigint() {
+        to_unsigned_bigint(items)?;
+    }
+    if options.hex_binary_text && is_binary(&column.data_type) {
+        hex_text_to_binary(items)?;
+    }
+    if options.collapse_midnight_timestamps {
+        collapse_midnight_timestamps(items);
+    }
+    if let Some(n) = column.multi_bit_width() {
+        bits_to_integer(items, n)?;
+    } else if column.data_type == DataType::Bit {
+        to_bit(items);
+    }
+    if let (Some(sign), DataType::TinyInt) = (options.tinyint_sign, column.data_type) {
+        normalize_tinyint(items, sign);
+    }
+    if let Some(target) = options.coercion.get(&DataTypeKind::from(&column.data_type)) {
+        coerce_items(items, *target)?;
+    }
+    if options.round_floats_to_scale {
+        if let Some(scale) = declared_scale(&column.data_type) {
+            let rounded = round_to_scale(items, scale);
+            if let Some(report) = &options.conversion_report {
+                report.record_rounded(&column.name, first_row, &rounded);
+            }
+        }
+    }
+    limit_cell_size(items, column, options)
+}
+
+/// Bind `cursor` with buffers sized by `options` and call `f` with every converted batch of rows,
+/// so no more than one batch (`max_batch_size` rows) is held in memory. Returns the row count.
+pub fn for_each_batch<C, F>(
+    cursor: C,
+    columns: &[OdbcColumn],
+    options: &Options,
+    mut f: F,
+) -> anyhow::Result<usize>
+where
+    C: Cursor,
+    F: FnMut(Vec<Vec<OdbcColumnItem>>) -> anyhow::Result<()>,
+{
+    let descs: Vec<BufferDesc> = columns
+        .iter()
+        .map(|c| <(&OdbcColumn, &Options) as TryConvert<BufferDesc>>::try_convert((c, options)))
+        .collect::<Result<_, _>>()
+        .map_err(|e| anyhow!(e))?;
+
+    let row_set_buffer =
+        ColumnarAnyBuffer::try_from_descs(options.max_batch_size, descs.iter().copied())?;
+    let mut row_set_cursor = cursor.bind_buffer(row_set_buffer)?;
+
+    let mut total = 0;
+    while let Some(row_set) = row_set_cursor
+        .fetch_with_truncation_check(options.error_for_truncation)
+        .map_err(|e| truncation_error(e, columns, &descs))?
+    {
+        let mut batch: Vec<Vec<OdbcColumnItem>> = (0..row_set.num_rows())
+            .map(|_| Vec::with_capacity(columns.len()))
+            .collect();
+        for (index, column) in columns.iter().enumerate() {
+            #[cfg(feature = "raw-trace")]
+            crate::extension::odbc::trace_raw_values(column, row_set.column(index));
+            let column_view: AnySlice = row_set.column(index);
+            let mut column_types = try_convert_view(column_view, column)?;
+            if options.strict_buffer_kind {
+                check_buffer_kind(&column_types, column)?;
+            }
+            post_process(&mut column_types, column, options, total)?;
+            for (row, c) in batch.iter_mut().zip(column_types) {
+                row.push(c);
+            }
+        }
+        total += batch.len();
+        f(batch)?;
+    }
+    Ok(total)
+}
+
+/// Fetch all rows, handing them to `sink` in chunks of at most `max_rows_in_memory` rows (at
+/// least 1), the last chunk may be smaller. Returns the number of rows fetched.
+///
+/// At most one chunk and one fetch batch of `options.max_batch_size` rows are in memory at a
+/// time, so exports of huge tables stay bounded without a custom streaming loop.
+pub fn fetch_bounded<C, F>(
+    cursor: C,
+    columns: &[OdbcColumn],
+    options: &Options,
+    max_rows_in_memory: usize,
+    mut sink: F,
+) -> anyhow::Result<usize>
+where
+    C: Cursor,
+    F: FnMut(&[Vec<OdbcColumnItem>]) -> anyhow::Result<()>,
+{
+    let max_rows = max_rows_in_memory.max(1);
+    let mut rows = Vec::with_capacity(max_rows.min(options.max_batch_size));
+    let total = for_each_batch(cursor, columns, options, |batch| {
+        for row in batch {
+            rows.push(row);
+            if rows.len() == max_rows {
+                sink(&rows)?;
+                rows.clear();
+            }
+        }
+        Ok(())
+    })?;
+    if !rows.is_empty() {
+        sink(&rows)?;
+    }
+    Ok(total)
+}
+
 impl<'a> OdbcDbConnection<'a> {
     pub fn new(conn: Connection<'a>, options: Options) -> anyhow::Result<Self> {
         let options = options.check();
@@ -207,10 +511,75 @@ impl<'a> OdbcDbConnection<'a> {
         Ok(result)
     }
 
+    /// Like `query`, but when a text/binary value is truncated the statement is executed again
+    /// with `max_str_len`/`max_binary_len` doubled, up to `max_buffer_len`.
+    /// ODBC cursors are forward only, so a re-fetch re-executes the statement:
+    /// only use it for queries without side effects.
+    pub fn fetch_and_convert<S>(
+        &self,
+        stmt: S,
+        max_buffer_len: usize,
+    ) -> anyhow::Result<QueryResult>
+    where
+        S: StatementInput,
+    {
+        let sql = stmt.to_sql().to_string();
+        match stmt.input_values()? {
+            Either::Left(params) => self.query_growing_buffers(&sql, &params[..], max_buffer_len),
+            Either::Right(_) => self.query_growing_buffers(&sql, (), max_buffer_len),
+        }
+    }
+
+    fn query_growing_buffers<P>(
+        &self,
+        sql: &str,
+        params: P,
+        max_buffer_len: usize,
+    ) -> anyhow::Result<QueryResult>
+    where
+        P: ParameterCollectionRef + Copy,
+    {
+        let mut options = self.options.clone();
+        options.error_for_truncation = true;
+        loop {
+            match self.query_result_with(sql, params, &options) {
+                Err(e)
+                    if matches!(
+                        e.downcast_ref::<OdbcHelperError>(),
+                        Some(OdbcHelperError::TruncatedError(_))
+                    ) =>
+                {
+                    if options.max_str_len >= max_buffer_len
+                        && options.max_binary_len >= max_buffer_len
+                    {
+                        return Err(e);
+                    }
+                    let grow = |len: usize| len.saturating_mul(2).min(max_buffer_len).max(len);
+                    options.max_str_len = grow(options.max_str_len);
+                    options.max_binary_len = grow(options.max_binary_len);
+                    warn!(
+                        "{}, retry with max_str_len:{} max_binary_len:{}",
+                        e, options.max_str_len, options.max_binary_len
+                    );
+                }
+                result => return result,
+            }
+        }
+    }
+
     fn query_result(
         &self,
         sql: &str,
         params: impl ParameterCollectionRef,
+    ) -> anyhow::Result<QueryResult> {
+        self.query_result_with(sql, params, &self.options)
+    }
+
+    fn query_result_with(
+        &self,
+        sql: &str,
+        params: impl ParameterCollectionRef,
+        options: &Options,
     ) -> anyhow::Result<QueryResult> {
         let mut cursor = self
             .conn
@@ -220,51 +589,22 @@ impl<'a> OdbcDbConnection<'a> {
         let mut query_result = Self::get_cursor_columns(&mut cursor)?;
         debug!("columns:{:?}", query_result.columns);
 
-        let descs = query_result.columns.iter().map(|c| {
-            <(&OdbcColumn, &Options) as TryConvert<BufferDesc>>::try_convert((c, &self.options))
-                .unwrap()
-        });
-
-        let row_set_buffer =
-            ColumnarAnyBuffer::try_from_descs(self.options.max_batch_size, descs).unwrap();
-
-        let mut row_set_cursor = cursor.bind_buffer(row_set_buffer).unwrap();
-
-        let mut total_row = vec![];
-        while let Some(row_set) = row_set_cursor.fetch()? {
-            for index in 0..query_result.columns.len() {
-                let column_view: AnySlice = row_set.column(index);
-                let column_types: Vec<OdbcColumnItem> = column_view.convert();
-                if index == 0 {
-                    for c in column_types.into_iter() {
-                        total_row.push(vec![c]);
-                    }
-                } else {
-                    for (col_index, c) in column_types.into_iter().enumerate() {
-                        let row = total_row.index_mut(col_index);
-                        row.push(c)
-                    }
-                }
-            }
-        }
-        query_result.data = total_row;
+        let mut data = vec![];
+        for_each_batch(cursor, &query_result.columns, options, |batch| {
+            data.extend(batch);
+            Ok(())
+        })?;
+        query_result.data = data;
         Ok(query_result)
     }
 
-    fn get_cursor_columns(cursor: &mut CursorImpl<StatementImpl>) -> anyhow::Result<QueryResult> {
-        let mut query_result = QueryResult::default();
-        for index in 0..cursor.num_result_cols()?.try_into()? {
-            let mut column_description = ColumnDescription::default();
-            cursor.describe_col(index + 1, &mut column_description)?;
-
-            let column = OdbcColumn::new(
-                column_description.name_to_string()?,
-                column_description.data_type,
-                column_description.could_be_nullable(),
-            );
-            query_result.columns.push(column);
-        }
-        Ok(query_result)
+    pub fn get_cursor_columns(
+        cursor: &mut CursorImpl<StatementImpl>,
+    ) -> anyhow::Result<QueryResult> {
+        Ok(QueryResult {
+            columns: cursor_columns(cursor)?,
+            ..Default::default()
+        })
     }
 
     fn table_desc(
@@ -289,3 +629,73 @@ impl<'a> OdbcDbConnection<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures;
+    use crate::odbc_api::DataType;
+
+    #[test]
+    fn test_limit_cell_size() {
+        let column = OdbcColumn::new("note".to_string(), DataType::Varchar { length: 0 }, true);
+        let mut options = Options::new(SupportDatabase::Pg);
+        options.max_cell_bytes = 4;
+        let mut items = vec![
+            fixtures::item(OdbcColumnType::Text, Some("ab\u{00e9}cd")),
+            fixtures::item(OdbcColumnType::Text, Some("abc")),
+        ];
+        limit_cell_size(&mut items, &column, &options).unwrap();
+        assert_eq!(items[0].value_to_string().unwrap(), "ab\u{00e9}");
+        assert_eq!(items[1].value_to_string().unwrap(), "abc");
+
+        options.max_cell_bytes = 2;
+        options.error_for_oversized_cell = true;
+        let err = limit_cell_size(&mut items, &column, &options).unwrap_err();
+        assert!(matches!(err, OdbcHelperError::CellTooLarge { size: 4, .. }));
+    }
+
+    #[test]
+    fn test_fetch_buffer_bytes() {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, false),
+            OdbcColumn::new("note".to_string(), DataType::Varchar { length: 4000 }, true),
+        ];
+        let mut options = Options::new(SupportDatabase::Pg);
+        let bytes = fetch_buffer_bytes(&columns, &options).unwrap();
+        assert!(bytes > options.max_batch_size * options.max_str_len);
+
+        options.max_batch_size *= 2;
+        assert_eq!(fetch_buffer_bytes(&columns, &options).unwrap(), bytes * 2);
+    }
+
+    #[test]
+    fn test_post_process_keeps_numeric_looking_text() {
+        let mut options = Options::new(SupportDatabase::Pg);
+        options.strip_bom = true;
+        options.hex_binary_text = true;
+        options.collapse_midnight_timestamps = true;
+        options.tinyint_sign = Some(TinyIntSign::Unsigned);
+        options.round_floats_to_scale = true;
+        for kind in [
+            DataTypeKind::Integer,
+            DataTypeKind::Numeric,
+            DataTypeKind::Decimal,
+        ] {
+            options.coercion.insert(kind, OdbcColumnType::I64);
+        }
+
+        for data_type in [
+            DataType::Char { length: 5 },
+            DataType::Varchar { length: 5 },
+            DataType::WVarchar { length: 5 },
+            DataType::LongVarchar { length: 0 },
+        ] {
+            let column = OdbcColumn::new("zip".to_string(), data_type, true);
+            let text = fixtures::item(OdbcColumnType::Text, Some("00123"));
+            let mut items = vec![text.clone()];
+            post_process(&mut items, &column, &options, 0).unwrap();
+            assert_eq!(items, vec![text]);
+        }
+    }
+}
@@ -2,10 +2,11 @@ pub mod batch;
 pub mod database;
 pub mod execute;
 pub mod query;
+pub mod row;
 pub mod statement;
 pub mod table;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SupportDatabase {
     Dameng,
     Pg,
@@ -1,6 +1,16 @@
-use crate::extension::odbc::{OdbcColumn, OdbcColumnItem};
-use odbc_common::print_table::Print;
+use crate::error::OdbcHelperError;
+use crate::executor::row::{Row, RowMapper};
+use crate::export::binary::{read_bin, write_bin};
+use crate::export::{CellRenderer, PrintOptions, TableRenderer, ROW_NUMBER_HEADER};
+use crate::extension::odbc::{
+    estimated_size_bytes, type_display, ColumnIndex, OdbcColumn, OdbcColumnItem, OdbcColumnType,
+};
+use crate::odbc_api::DataType;
+use odbc_common::print_table::{
+    display_width, draw_table_with_width, simple_table_string, table_width, Print,
+};
 use odbc_common::{StyledString, Table, TableTheme, TextStyle};
+use std::io::{self, Read, Write};
 
 #[derive(Debug, Default)]
 pub struct QueryResult {
@@ -10,20 +20,268 @@ pub struct QueryResult {
     pub data: Vec<Vec<OdbcColumnItem>>,
 }
 
+impl QueryResult {
+    /// Serialize the result set with the length-prefixed binary framing, see [`write_bin`].
+    pub fn write_bin<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_bin(w, &self.columns, &self.data)
+    }
+
+    /// Load a result set written by [`QueryResult::write_bin`].
+    pub fn read_bin<R: Read>(r: &mut R) -> io::Result<Self> {
+        let (columns, data) = read_bin(r)?;
+        Ok(QueryResult { columns, data })
+    }
+
+    /// Estimated in-memory size of the row data, see [`estimated_size_bytes`].
+    pub fn estimated_size_bytes(&self) -> usize {
+        estimated_size_bytes(&self.data)
+    }
+
+    /// Tag the text cells of the named columns as `OdbcColumnType::Json`, so the JSON exporter
+    /// inlines them instead of writing a quoted string. Use it for columns known to hold JSON,
+    /// e.g. Postgres `JSON`/`JSONB` (see `pg_json_columns`) or SQL Server `NVARCHAR` JSON.
+    /// Cells that are not valid JSON are left as text.
+    pub fn tag_json_columns<S: AsRef<str>>(&mut self, names: &[S]) {
+        let indexes = self
+            .columns
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| names.iter().any(|n| n.as_ref() == c.name))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        for row in self.data.iter_mut() {
+            for index in indexes.iter() {
+                let item = match row.get_mut(*index) {
+                    Some(item) => item,
+                    None => continue,
+                };
+                if !matches!(item.odbc_type, OdbcColumnType::Text | OdbcColumnType::WText) {
+                    continue;
+                }
+                if let Some(value) = &item.value {
+                    if let Err(e) = serde_json::from_slice::<serde_json::Value>(value) {
+                        warn!(
+                            "column `{}` is not valid json:{}",
+                            self.columns[*index].name, e
+                        );
+                        continue;
+                    }
+                }
+                item.odbc_type = OdbcColumnType::Json;
+            }
+        }
+    }
+
+    /// Map every row with `mapper`, see [`map_rows`](crate::executor::row::map_rows) to map
+    /// straight from a cursor.
+    pub fn map_rows<T, M: RowMapper<T>>(&self, mapper: M) -> Result<Vec<T>, OdbcHelperError> {
+        let index = ColumnIndex::new(&self.columns);
+        self.data
+            .iter()
+            .map(|items| mapper.map_row(&Row::new(&index, items)))
+            .collect()
+    }
+
+    /// Attach `PrintOptions` used when rendering the table.
+    pub fn with_options(self, options: &PrintOptions) -> QueryResultPrinter<'_> {
+        QueryResultPrinter {
+            result: self,
+            options,
+        }
+    }
+}
+
 impl Print for QueryResult {
     fn convert_table(self) -> anyhow::Result<Table> {
-        let headers: Vec<StyledString> = self
-            .columns
+        let options = PrintOptions::default();
+        self.with_options(&options).convert_table()
+    }
+}
+
+/// `QueryResult` printed with custom `PrintOptions`, see [`QueryResult::with_options`].
+#[derive(Debug)]
+pub struct QueryResultPrinter<'a> {
+    result: QueryResult,
+    options: &'a PrintOptions,
+}
+
+impl QueryResultPrinter<'_> {
+    fn rendered_rows(&self) -> Vec<Vec<String>> {
+        self.result
+            .data
             .iter()
-            .map(|x| StyledString::new(x.name.to_string(), TextStyle::default_header()))
+            .enumerate()
+            .map(|(index, x)| {
+                let row_number = self
+                    .options
+                    .with_row_numbers
+                    .then(|| (index + 1).to_string());
+                row_number
+                    .into_iter()
+                    .chain(
+                        x.iter()
+                            .zip(self.result.columns.iter())
+                            .map(|(y, column)| self.options.render(y, column)),
+                    )
+                    .map(|cell| self.pad(self.widen(cell)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Without a header row `min_col_width` is applied to the cells instead, left-aligned like
+    /// the cells themselves.
+    fn widen(&self, cell: String) -> String {
+        let fill = self
+            .options
+            .min_col_width
+            .saturating_sub(display_width(&cell));
+        if self.options.write_header || fill == 0 {
+            return cell;
+        }
+        cell + &" ".repeat(fill)
+    }
+
+    /// Empty when `write_header` is false, the renderers then draw the rows only.
+    fn headers(&self) -> Vec<String> {
+        if !self.options.write_header {
+            return vec![];
+        }
+        let row_number = self
+            .options
+            .with_row_numbers
+            .then(|| ROW_NUMBER_HEADER.to_string());
+        row_number
+            .into_iter()
+            .chain(self.result.columns.iter().map(|x| x.name.to_string()))
+            .map(|header| {
+                let width = self.options.min_col_width;
+                self.pad(format!("{:^width$}", header, width = width))
+            })
+            .collect()
+    }
+
+    /// `padding_left`/`padding_right` spaces around `cell`. With a header `min_col_width` only
+    /// needs to be applied to the headers, the renderers widen a column to its widest cell.
+    fn pad(&self, cell: String) -> String {
+        if self.options.padding_left == 0 && self.options.padding_right == 0 {
+            return cell;
+        }
+        format!(
+            "{}{}{}",
+            " ".repeat(self.options.padding_left),
+            cell,
+            " ".repeat(self.options.padding_right)
+        )
+    }
+}
+
+impl Print for QueryResultPrinter<'_> {
+    fn convert_table(self) -> anyhow::Result<Table> {
+        let headers: Vec<StyledString> = self
+            .headers()
+            .into_iter()
+            .map(|x| StyledString::new(x, TextStyle::default_header()))
             .collect();
 
         let rows = self
-            .data
+            .rendered_rows()
+            .into_iter()
+            .map(|x| {
+                x.into_iter()
+                    .map(|y| StyledString::new(y, TextStyle::basic_left()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        Ok(Table::new(headers, rows, TableTheme::rounded()))
+    }
+
+    fn table_string(self) -> anyhow::Result<String> {
+        match self.options.renderer {
+            TableRenderer::Nu => {
+                let width = table_width(self.options.terminal_width);
+                draw_table_with_width(self.convert_table()?, width)
+            }
+            TableRenderer::Simple => {
+                Ok(simple_table_string(&self.headers(), &self.rendered_rows()))
+            }
+        }
+    }
+}
+
+/// Render the schema of a result set, like `\d`: one row per column with its name, SQL type
+/// (see `type_display`), nullability and precision/scale for the types that have them.
+/// A `comment` column is added when any column has a comment.
+pub fn print_schema(columns: &[OdbcColumn]) -> anyhow::Result<String> {
+    SchemaPrinter { columns }.table_string()
+}
+
+/// The rows of [`print_schema`], see it for the columns.
+#[derive(Debug)]
+pub struct SchemaPrinter<'a> {
+    pub columns: &'a [OdbcColumn],
+}
+
+impl SchemaPrinter<'_> {
+    const HEADERS: [&'static str; 5] = ["name", "type", "nullable", "precision", "scale"];
+
+    fn has_comments(&self) -> bool {
+        self.columns.iter().any(|c| c.comment.is_some())
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        let mut headers = Self::HEADERS.to_vec();
+        if self.has_comments() {
+            headers.push("comment");
+        }
+        headers
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let has_comments = self.has_comments();
+        self.columns
             .iter()
+            .map(|column| {
+                let (precision, scale) = match column.data_type {
+                    DataType::Numeric { precision, scale }
+                    | DataType::Decimal { precision, scale } => {
+                        (precision.to_string(), scale.to_string())
+                    }
+                    DataType::Float { precision } => (precision.to_string(), String::new()),
+                    DataType::Time { precision } | DataType::Timestamp { precision } => {
+                        (precision.to_string(), String::new())
+                    }
+                    _ => (String::new(), String::new()),
+                };
+                let nullable = if column.nullable { "YES" } else { "NO" };
+                let mut row = vec![
+                    column.name.clone(),
+                    type_display(&column.data_type),
+                    nullable.to_string(),
+                    precision,
+                    scale,
+                ];
+                if has_comments {
+                    row.push(column.comment.clone().unwrap_or_default());
+                }
+                row
+            })
+            .collect()
+    }
+}
+
+impl Print for SchemaPrinter<'_> {
+    fn convert_table(self) -> anyhow::Result<Table> {
+        let headers = self
+            .headers()
+            .iter()
+            .map(|x| StyledString::new(x.to_string(), TextStyle::default_header()))
+            .collect();
+        let rows = self
+            .rows()
+            .into_iter()
             .map(|x| {
-                x.iter()
-                    .map(|y| y.to_string())
+                x.into_iter()
                     .map(|y| StyledString::new(y, TextStyle::basic_left()))
                     .collect::<Vec<_>>()
             })
@@ -31,3 +289,101 @@ impl Print for QueryResult {
         Ok(Table::new(headers, rows, TableTheme::rounded()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures;
+
+    #[test]
+    fn test_print_schema() {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, false),
+            OdbcColumn::new(
+                "price".to_string(),
+                DataType::Decimal {
+                    precision: 18,
+                    scale: 4,
+                },
+                true,
+            ),
+        ];
+        let rows = SchemaPrinter { columns: &columns }.rows();
+        assert_eq!(rows[0], vec!["id", "INTEGER", "NO", "", ""]);
+        assert_eq!(rows[1], vec!["price", "DECIMAL(18,4)", "YES", "18", "4"]);
+
+        let table = print_schema(&columns).unwrap();
+        assert!(table.contains("DECIMAL(18,4)"));
+        assert!(table.contains("nullable"));
+        assert!(!table.contains("comment"));
+
+        let columns = vec![columns[0].clone().with_comment("primary key")];
+        let rows = SchemaPrinter { columns: &columns }.rows();
+        assert_eq!(rows[0], vec!["id", "INTEGER", "NO", "", "", "primary key"]);
+        assert!(print_schema(&columns).unwrap().contains("comment"));
+    }
+
+    #[test]
+    fn test_min_col_width_and_padding() {
+        let result = QueryResult {
+            columns: vec![OdbcColumn::new(
+                "s".to_string(),
+                DataType::Char { length: 1 },
+                true,
+            )],
+            data: vec![vec![fixtures::item(OdbcColumnType::Text, Some("A"))]],
+        };
+        let options = PrintOptions {
+            renderer: TableRenderer::Simple,
+            min_col_width: 6,
+            padding_left: 1,
+            padding_right: 1,
+            ..Default::default()
+        };
+        let printer = result.with_options(&options);
+        assert_eq!(printer.headers(), vec!["   s    "]);
+        assert_eq!(printer.rendered_rows(), vec![vec![" A "]]);
+        assert_eq!(printer.table_string().unwrap(), "   s\n--------\n A");
+    }
+
+    #[test]
+    fn test_print_without_header() {
+        let result = QueryResult {
+            columns: vec![OdbcColumn::new("id".to_string(), DataType::Integer, false)],
+            data: vec![vec![fixtures::item(OdbcColumnType::I32, Some("7"))]],
+        };
+        let options = PrintOptions {
+            write_header: false,
+            ..Default::default()
+        };
+        let table = result.with_options(&options).table_string().unwrap();
+        assert!(table.contains('7'));
+        assert!(!table.contains("id"));
+    }
+
+    #[test]
+    fn test_min_col_width_without_header() {
+        let result = QueryResult {
+            columns: vec![
+                OdbcColumn::new("a".to_string(), DataType::Char { length: 1 }, true),
+                OdbcColumn::new("b".to_string(), DataType::Char { length: 2 }, true),
+            ],
+            data: vec![vec![
+                fixtures::item(OdbcColumnType::Text, Some("A")),
+                fixtures::item(OdbcColumnType::WText, Some("世界")),
+            ]],
+        };
+        let options = PrintOptions {
+            renderer: TableRenderer::Simple,
+            min_col_width: 6,
+            padding_left: 1,
+            padding_right: 1,
+            write_header: false,
+            ..Default::default()
+        };
+        let printer = result.with_options(&options);
+        assert!(printer.headers().is_empty());
+        assert_eq!(printer.rendered_rows(), vec![vec![" A      ", " 世界   "]]);
+        assert_eq!(printer.table_string().unwrap(), " A       |  世界");
+    }
+}
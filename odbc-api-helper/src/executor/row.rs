@@ -0,0 +1,296 @@
+//! Map fetched rows into user types, e.g.
+//! `map_rows(cursor, &options, |r: &Row| Ok(User { id: r.get("id")?, name: r.get("name")? }))`.
+use crate::error::OdbcHelperError;
+use crate::executor::database::{cursor_columns, for_each_batch, Options};
+use crate::extension::odbc::{ColumnIndex, OdbcColumn, OdbcColumnItem, OdbcColumnType};
+use crate::extension::util::{parse_to_data_time, parse_to_date, parse_to_time};
+use crate::odbc_api::Cursor;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+/// Conversion of a single cell into a Rust value, see [`Row::get`].
+///
+/// A NULL cell is an error unless the target is an `Option`.
+pub trait FromColumnItem: Sized {
+    fn from_item(item: &OdbcColumnItem) -> Result<Self, OdbcHelperError>;
+}
+
+fn conversion_error(item: &OdbcColumnItem, target: &str) -> OdbcHelperError {
+    OdbcHelperError::TypeConversionError(format!(
+        "{} from {:?} `{}`",
+        target,
+        item.odbc_type,
+        item.value_to_string().unwrap_or_else(|| "NULL".to_string())
+    ))
+}
+
+fn text(item: &OdbcColumnItem, target: &str) -> Result<String, OdbcHelperError> {
+    item.value_to_string()
+        .ok_or_else(|| conversion_error(item, target))
+}
+
+macro_rules! impl_from_column_item_number {
+    ($($t:ty),*) => {
+        $(
+            impl FromColumnItem for $t {
+                fn from_item(item: &OdbcColumnItem) -> Result<Self, OdbcHelperError> {
+                    let target = stringify!($t);
+                    text(item, target)?
+                        .trim()
+                        .parse::<$t>()
+                        .map_err(|_| conversion_error(item, target))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_column_item_number!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+
+/// `Bit` and integer cells, zero is false.
+impl FromColumnItem for bool {
+    fn from_item(item: &OdbcColumnItem) -> Result<Self, OdbcHelperError> {
+        match text(item, "bool")?.as_str() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            v if item.odbc_type != OdbcColumnType::Bit => v
+                .parse::<i64>()
+                .map(|v| v != 0)
+                .map_err(|_| conversion_error(item, "bool")),
+            _ => Err(conversion_error(item, "bool")),
+        }
+    }
+}
+
+/// The display text of any cell, `Binary` is hex.
+impl FromColumnItem for String {
+    fn from_item(item: &OdbcColumnItem) -> Result<Self, OdbcHelperError> {
+        text(item, "String")
+    }
+}
+
+/// The raw bytes of any cell.
+impl FromColumnItem for Vec<u8> {
+    fn from_item(item: &OdbcColumnItem) -> Result<Self, OdbcHelperError> {
+        item.value
+            .as_ref()
+            .map(|v| v.to_vec())
+            .ok_or_else(|| conversion_error(item, "Vec<u8>"))
+    }
+}
+
+macro_rules! impl_from_column_item_chrono {
+    ($($t:ty => $parse:ident),*) => {
+        $(
+            impl FromColumnItem for $t {
+                fn from_item(item: &OdbcColumnItem) -> Result<Self, OdbcHelperError> {
+                    let target = stringify!($t);
+                    let value = item
+                        .value
+                        .clone()
+                        .ok_or_else(|| conversion_error(item, target))?;
+                    $parse(value).map_err(|_| conversion_error(item, target))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_column_item_chrono!(
+    NaiveDate => parse_to_date,
+    NaiveTime => parse_to_time,
+    NaiveDateTime => parse_to_data_time
+);
+
+impl<T: FromColumnItem> FromColumnItem for Option<T> {
+    fn from_item(item: &OdbcColumnItem) -> Result<Self, OdbcHelperError> {
+        if item.is_null() {
+            return Ok(None);
+        }
+        T::from_item(item).map(Some)
+    }
+}
+
+/// One fetched row with lookup by column name.
+#[derive(Debug, Clone, Copy)]
+pub struct Row<'a> {
+    index: &'a ColumnIndex,
+    items: &'a [OdbcColumnItem],
+}
+
+impl<'a> Row<'a> {
+    pub fn new(index: &'a ColumnIndex, items: &'a [OdbcColumnItem]) -> Self {
+        Row { index, items }
+    }
+
+    pub fn items(&self) -> &'a [OdbcColumnItem] {
+        self.items
+    }
+
+    /// The value of the column `name`, see [`FromColumnItem`].
+    pub fn get<T: FromColumnItem>(&self, name: &str) -> Result<T, OdbcHelperError> {
+        let item = self
+            .index
+            .get(self.items, name)
+            .ok_or_else(|| OdbcHelperError::ColumnNotFound(name.to_string()))?;
+        T::from_item(item).map_err(|e| match e {
+            OdbcHelperError::TypeConversionError(e) => {
+                OdbcHelperError::TypeConversionError(format!("{}, column `{}`", e, name))
+            }
+            e => e,
+        })
+    }
+
+    /// The value of the column at `index` (starting at 0).
+    pub fn get_at<T: FromColumnItem>(&self, index: usize) -> Result<T, OdbcHelperError> {
+        let item = self
+            .items
+            .get(index)
+            .ok_or_else(|| OdbcHelperError::ColumnNotFound(index.to_string()))?;
+        T::from_item(item)
+    }
+}
+
+/// Conversion of a [`Row`] into `T`, implemented for closures `Fn(&Row) -> Result<T, _>`.
+pub trait RowMapper<T> {
+    fn map_row(&self, row: &Row) -> Result<T, OdbcHelperError>;
+}
+
+impl<T, F> RowMapper<T> for F
+where
+    F: Fn(&Row) -> Result<T, OdbcHelperError>,
+{
+    fn map_row(&self, row: &Row) -> Result<T, OdbcHelperError> {
+        self(row)
+    }
+}
+
+/// The cells of a single column result, e.g. `SELECT id FROM users`, converted into `T`.
+/// Any other column count is an `OdbcHelperError::NotSingleColumn`.
+pub fn column_into<T: FromColumnItem>(
+    columns: &[OdbcColumn],
+    rows: Vec<Vec<OdbcColumnItem>>,
+) -> Result<Vec<T>, OdbcHelperError> {
+    if columns.len() != 1 {
+        return Err(OdbcHelperError::NotSingleColumn(columns.len()));
+    }
+    rows.iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let item = row.first().ok_or(OdbcHelperError::NotSingleColumn(0))?;
+            T::from_item(item).map_err(|e| match e {
+                OdbcHelperError::TypeConversionError(e) => {
+                    OdbcHelperError::TypeConversionError(format!("{}, row {}", e, index))
+                }
+                e => e,
+            })
+        })
+        .collect()
+}
+
+/// Fetch every batch of `cursor` and map its rows with `mapper`, the first error stops.
+pub fn map_rows<C, T, M>(mut cursor: C, options: &Options, mapper: M) -> anyhow::Result<Vec<T>>
+where
+    C: Cursor,
+    M: RowMapper<T>,
+{
+    let columns = cursor_columns(&mut cursor)?;
+    let index = ColumnIndex::new(&columns);
+    let mut mapped = vec![];
+    for_each_batch(cursor, &columns, options, |batch| {
+        for items in batch.iter() {
+            mapped.push(mapper.map_row(&Row::new(&index, items))?);
+        }
+        Ok(())
+    })?;
+    Ok(mapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::query::QueryResult;
+    use crate::export::fixtures::{self, item};
+    use crate::odbc_api::DataType;
+
+    #[derive(Debug, PartialEq)]
+    struct User {
+        id: i64,
+        name: Option<String>,
+        active: bool,
+    }
+
+    #[test]
+    fn test_map_rows() {
+        let result = QueryResult {
+            columns: vec![
+                OdbcColumn::new("id".to_string(), DataType::BigInt, false),
+                OdbcColumn::new("name".to_string(), DataType::Varchar { length: 10 }, true),
+                OdbcColumn::new("active".to_string(), DataType::Bit, false),
+            ],
+            data: vec![
+                vec![
+                    item(OdbcColumnType::I64, Some("1")),
+                    item(OdbcColumnType::Text, Some("foo")),
+                    item(OdbcColumnType::Bit, Some("true")),
+                ],
+                vec![
+                    item(OdbcColumnType::I64, Some("2")),
+                    item(OdbcColumnType::Text, None),
+                    item(OdbcColumnType::Bit, Some("false")),
+                ],
+            ],
+        };
+
+        let users = result
+            .map_rows(|r: &Row| {
+                Ok(User {
+                    id: r.get("id")?,
+                    name: r.get("name")?,
+                    active: r.get("active")?,
+                })
+            })
+            .unwrap();
+        assert_eq!(
+            users,
+            vec![
+                User {
+                    id: 1,
+                    name: Some("foo".to_string()),
+                    active: true
+                },
+                User {
+                    id: 2,
+                    name: None,
+                    active: false
+                },
+            ]
+        );
+
+        let error = result
+            .map_rows(|r: &Row| r.get::<String>("name"))
+            .unwrap_err();
+        assert!(error.to_string().contains("column `name`"));
+        let error = result
+            .map_rows(|r: &Row| r.get::<i64>("missing"))
+            .unwrap_err();
+        assert!(matches!(error, OdbcHelperError::ColumnNotFound(_)));
+    }
+
+    #[test]
+    fn test_column_into() {
+        let columns = vec![OdbcColumn::new("id".to_string(), DataType::BigInt, true)];
+        let row = |v: Option<&str>| vec![fixtures::item(OdbcColumnType::I64, v)];
+        let ids: Vec<i64> = column_into(&columns, vec![row(Some("1")), row(Some("2"))]).unwrap();
+        assert_eq!(ids, vec![1, 2]);
+        let ids: Vec<Option<i64>> = column_into(&columns, vec![row(None)]).unwrap();
+        assert_eq!(ids, vec![None]);
+
+        let error = column_into::<i64>(&columns, vec![row(Some("1")), row(None)]).unwrap_err();
+        assert!(error.to_string().ends_with("row 1"));
+        let two = vec![columns[0].clone(), columns[0].clone()];
+        assert!(matches!(
+            column_into::<i64>(&two, vec![]),
+            Err(OdbcHelperError::NotSingleColumn(2))
+        ));
+    }
+}
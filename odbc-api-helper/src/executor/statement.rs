@@ -1,6 +1,8 @@
 use crate::error::OdbcHelperError;
 use crate::executor::batch::{OdbcOperation, Operation};
-use crate::odbc_api::parameter::InputParameter;
+use crate::odbc_api::parameter::{InputParameter, VarBinaryBox, VarCharBox, WithDataType};
+use crate::odbc_api::sys::{Date, Time, Timestamp};
+use crate::odbc_api::{Bit, DataType, Nullable};
 use crate::TryConvert;
 use either::Either;
 use std::any::Any;
@@ -125,6 +127,67 @@ impl StatementInput for String {
     }
 }
 
+/// A NULL input parameter bound with the SQL type `data_type`, e.g. of the target column.
+///
+/// Strict drivers reject a NULL bound as text for a column of another type. Integers, floats,
+/// `BIT`, dates and times use their own C type, binary types `VarBinary`, everything else
+/// (text, decimals, ...) `VarChar`.
+pub fn null_param(data_type: &DataType) -> Box<dyn InputParameter> {
+    let data_type = *data_type;
+    match data_type {
+        DataType::TinyInt => Box::new(WithDataType {
+            value: Nullable::<i8>::null(),
+            data_type,
+        }),
+        DataType::SmallInt => Box::new(WithDataType {
+            value: Nullable::<i16>::null(),
+            data_type,
+        }),
+        DataType::Integer => Box::new(WithDataType {
+            value: Nullable::<i32>::null(),
+            data_type,
+        }),
+        DataType::BigInt => Box::new(WithDataType {
+            value: Nullable::<i64>::null(),
+            data_type,
+        }),
+        DataType::Real => Box::new(WithDataType {
+            value: Nullable::<f32>::null(),
+            data_type,
+        }),
+        DataType::Float { .. } | DataType::Double => Box::new(WithDataType {
+            value: Nullable::<f64>::null(),
+            data_type,
+        }),
+        DataType::Bit => Box::new(WithDataType {
+            value: Nullable::<Bit>::null(),
+            data_type,
+        }),
+        DataType::Date => Box::new(WithDataType {
+            value: Nullable::<Date>::null(),
+            data_type,
+        }),
+        DataType::Time { .. } => Box::new(WithDataType {
+            value: Nullable::<Time>::null(),
+            data_type,
+        }),
+        DataType::Timestamp { .. } => Box::new(WithDataType {
+            value: Nullable::<Timestamp>::null(),
+            data_type,
+        }),
+        DataType::Binary { .. } | DataType::Varbinary { .. } | DataType::LongVarbinary { .. } => {
+            Box::new(WithDataType {
+                value: VarBinaryBox::null(),
+                data_type,
+            })
+        }
+        _ => Box::new(WithDataType {
+            value: VarCharBox::null(),
+            data_type,
+        }),
+    }
+}
+
 /// TryConvert State `StatementInput` trait to `EitherBoxParams`
 /// # Example
 ///
@@ -174,3 +237,36 @@ impl<T: StatementInput> TryConvert<EitherInputParameter> for T {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::odbc_api::handles::{CData, HasDataType};
+    use crate::odbc_api::sys::{CDataType, NULL_DATA};
+
+    #[test]
+    fn test_null_param() {
+        for (data_type, c_type) in [
+            (DataType::Integer, CDataType::SLong),
+            (DataType::Double, CDataType::Double),
+            (DataType::Bit, CDataType::Bit),
+            (
+                DataType::Timestamp { precision: 3 },
+                CDataType::TypeTimestamp,
+            ),
+            (DataType::Varbinary { length: 16 }, CDataType::Binary),
+            (
+                DataType::Decimal {
+                    precision: 10,
+                    scale: 2,
+                },
+                CDataType::Char,
+            ),
+        ] {
+            let param = null_param(&data_type);
+            assert_eq!(param.data_type(), data_type);
+            assert_eq!(param.cdata_type(), c_type);
+            assert_eq!(unsafe { *param.indicator_ptr() }, NULL_DATA);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Totals of numeric columns accumulated while rows are exported, for a footer row without a
+//! second pass over the data.
+use crate::extension::odbc::{OdbcColumn, OdbcColumnItem, OdbcColumnType};
+use bytes::BytesMut;
+
+/// Aggregate shown in the footer row of [`Aggregator::footer`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Aggregate {
+    /// Default.
+    #[default]
+    Sum,
+    Count,
+    Avg,
+    Min,
+    Max,
+}
+
+/// Running aggregates of one column, over its non-NULL numeric values.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ColumnStats {
+    pub count: usize,
+    pub sum: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl ColumnStats {
+    pub fn avg(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+
+    fn get(&self, aggregate: Aggregate) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        match aggregate {
+            Aggregate::Sum => Some(self.sum),
+            Aggregate::Count => Some(self.count as f64),
+            Aggregate::Avg => self.avg(),
+            Aggregate::Min => self.min,
+            Aggregate::Max => self.max,
+        }
+    }
+}
+
+/// Feed every exported row with [`Aggregator::feed`], then append [`Aggregator::footer`] to
+/// the rows given to the table, CSV or any other exporter.
+///
+/// `Bit` cells count as 1/0, so the sum of a flag column is its number of trues. NULLs, NaN and
+/// other non-numeric cells are skipped, values are summed as `f64`.
+#[derive(Debug, Clone)]
+pub struct Aggregator {
+    stats: Vec<ColumnStats>,
+}
+
+impl Aggregator {
+    pub fn new(columns: &[OdbcColumn]) -> Self {
+        Aggregator {
+            stats: vec![ColumnStats::default(); columns.len()],
+        }
+    }
+
+    pub fn feed(&mut self, row: &[OdbcColumnItem]) {
+        for (stats, item) in self.stats.iter_mut().zip(row) {
+            let v = match item
+                .as_f64()
+                .or_else(|| item.bit_as_int().map(|v| v as f64))
+            {
+                Some(v) if !v.is_nan() => v,
+                _ => continue,
+            };
+            stats.count += 1;
+            stats.sum += v;
+            stats.min = Some(stats.min.map_or(v, |min| min.min(v)));
+            stats.max = Some(stats.max.map_or(v, |max| max.max(v)));
+        }
+    }
+
+    /// Aggregates per column, in column order.
+    pub fn stats(&self) -> &[ColumnStats] {
+        &self.stats
+    }
+
+    /// A row with `aggregate` of each numeric column, NULL for the other columns. `label` goes in
+    /// the first column when it has no numeric value, e.g. `total`.
+    pub fn footer(&self, aggregate: Aggregate, label: &str) -> Vec<OdbcColumnItem> {
+        self.stats
+            .iter()
+            .enumerate()
+            .map(|(index, stats)| match stats.get(aggregate) {
+                Some(_) if aggregate == Aggregate::Count => OdbcColumnItem {
+                    odbc_type: OdbcColumnType::I64,
+                    value: Some(BytesMut::from(stats.count.to_string().as_str())),
+                },
+                Some(v) => OdbcColumnItem {
+                    odbc_type: OdbcColumnType::F64,
+                    value: Some(BytesMut::from(v.to_string().as_str())),
+                },
+                None if index == 0 && !label.is_empty() => OdbcColumnItem {
+                    odbc_type: OdbcColumnType::Text,
+                    value: Some(BytesMut::from(label)),
+                },
+                None => OdbcColumnItem {
+                    odbc_type: OdbcColumnType::Text,
+                    value: None,
+                },
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures::item;
+    use crate::odbc_api::DataType;
+
+    #[test]
+    fn test_aggregator_footer() {
+        let columns = vec![
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 20 }, true),
+            OdbcColumn::new("qty".to_string(), DataType::Integer, true),
+            OdbcColumn::new("price".to_string(), DataType::Double, true),
+        ];
+        let rows = [
+            vec![
+                item(OdbcColumnType::Text, Some("a")),
+                item(OdbcColumnType::I32, Some("2")),
+                item(OdbcColumnType::F64, Some("1.5")),
+            ],
+            vec![
+                item(OdbcColumnType::Text, Some("b")),
+                item(OdbcColumnType::I32, None),
+                item(OdbcColumnType::F64, Some("2.5")),
+            ],
+            vec![
+                item(OdbcColumnType::Text, Some("c")),
+                item(OdbcColumnType::I32, Some("4")),
+                item(OdbcColumnType::F64, Some("NaN")),
+            ],
+        ];
+        let mut aggregator = Aggregator::new(&columns);
+        rows.iter().for_each(|row| aggregator.feed(row));
+
+        let cells = |aggregate| {
+            aggregator
+                .footer(aggregate, "total")
+                .iter()
+                .map(|item| item.value_to_string())
+                .collect::<Vec<_>>()
+        };
+        let some = |v: &str| Some(v.to_string());
+        assert_eq!(
+            cells(Aggregate::Sum),
+            vec![some("total"), some("6"), some("4")]
+        );
+        assert_eq!(
+            cells(Aggregate::Count),
+            vec![some("total"), some("2"), some("2")]
+        );
+        assert_eq!(
+            cells(Aggregate::Avg),
+            vec![some("total"), some("3"), some("2")]
+        );
+        assert_eq!(aggregator.stats()[2].max, Some(2.5));
+        assert_eq!(aggregator.stats()[0], ColumnStats::default());
+    }
+}
@@ -0,0 +1,435 @@
+//! Compact length-prefixed binary framing for caching a fetched result set.
+//!
+//! Layout (all integers little-endian):
+//! ```text
+//! magic "OBRS" | version u8
+//! column count u32 | per column: name len u32, name bytes, sql type i16,
+//!                    column size u64, decimal digits i16, nullable u8,
+//!                    auto increment opt, unsigned opt, declared length present u8,
+//!                    [declared length u64], comment present u8, [comment len u32, comment bytes]
+//! row count u64    | per cell: type tag u8, null flag u8, [value len u32, value bytes]
+//! ```
+//! `opt` is an `Option<bool>` as one byte: 0 is `None`, 1 is `false` and 2 is `true`. Version 1
+//! files, without the fields after `nullable`, are still read.
+//! Cell values are stored verbatim, so binary payloads and exact decimals round-trip losslessly.
+//!
+//! For pipelined protocols a single row can be framed on its own with [`encode_row`]:
+//! ```text
+//! cell count u32 | per cell: type tag u8, null flag u8, value len u32, value bytes
+//! ```
+//! A NULL cell has null flag 0 and value len 0.
+use crate::extension::odbc::{OdbcColumn, OdbcColumnItem, OdbcColumnType};
+use crate::odbc_api::{sys::SqlDataType, DataType};
+use bytes::BytesMut;
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 4] = b"OBRS";
+const VERSION: u8 = 2;
+// Lengths read from the stream are untrusted, e.g. of a corrupt cache file, allocations up front
+// are capped and larger collections grow as they are read.
+const MAX_PREALLOC: usize = 1 << 16;
+
+/// Write `columns` and `data` to `w` with the binary framing described in the module docs.
+pub fn write_bin<W: Write>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+) -> io::Result<()> {
+    w.write_all(MAGIC)?;
+    w.write_all(&[VERSION])?;
+
+    write_len(w, columns.len())?;
+    for column in columns {
+        write_bytes(w, column.name.as_bytes())?;
+        w.write_all(&column.data_type.data_type().0.to_le_bytes())?;
+        w.write_all(&(column.data_type.column_size() as u64).to_le_bytes())?;
+        w.write_all(&column.data_type.decimal_digits().to_le_bytes())?;
+        w.write_all(&[column.nullable as u8])?;
+        w.write_all(&[opt_bool_tag(column.auto_increment)])?;
+        w.write_all(&[opt_bool_tag(column.unsigned)])?;
+        match column.declared_length {
+            Some(length) => {
+                w.write_all(&[1])?;
+                w.write_all(&(length as u64).to_le_bytes())?;
+            }
+            None => w.write_all(&[0])?,
+        }
+        match &column.comment {
+            Some(comment) => {
+                w.write_all(&[1])?;
+                write_bytes(w, comment.as_bytes())?;
+            }
+            None => w.write_all(&[0])?,
+        }
+    }
+
+    w.write_all(&(data.len() as u64).to_le_bytes())?;
+    for row in data {
+        if row.len() != columns.len() {
+            return Err(invalid_data(format!(
+                "row has {} cells but schema has {} columns",
+                row.len(),
+                columns.len()
+            )));
+        }
+        for item in row {
+            w.write_all(&[type_tag(&item.odbc_type)])?;
+            match &item.value {
+                Some(value) => {
+                    w.write_all(&[1])?;
+                    write_bytes(w, value)?;
+                }
+                None => w.write_all(&[0])?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read a result set previously written by [`write_bin`].
+pub fn read_bin<R: Read>(r: &mut R) -> io::Result<(Vec<OdbcColumn>, Vec<Vec<OdbcColumnItem>>)> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid_data("missing result set magic header"));
+    }
+    let version = read_u8(r)?;
+    if version != 1 && version != VERSION {
+        return Err(invalid_data(format!("unsupported version:{}", version)));
+    }
+
+    let column_len = read_u32(r)? as usize;
+    let mut columns = Vec::with_capacity(column_len.min(MAX_PREALLOC));
+    for _ in 0..column_len {
+        let name = String::from_utf8(read_bytes(r)?).map_err(invalid_data)?;
+        let sql_type = SqlDataType(i16::from_le_bytes(read_array(r)?));
+        let column_size = u64::from_le_bytes(read_array(r)?) as usize;
+        let decimal_digits = i16::from_le_bytes(read_array(r)?);
+        let nullable = read_u8(r)? != 0;
+        let data_type = DataType::new(sql_type, column_size, decimal_digits);
+        let mut column = OdbcColumn::new(name, data_type, nullable);
+        if version >= 2 {
+            column.auto_increment = read_opt_bool(r)?;
+            column.unsigned = read_opt_bool(r)?;
+            column.declared_length = match read_u8(r)? {
+                0 => None,
+                1 => Some(u64::from_le_bytes(read_array(r)?) as usize),
+                flag => return Err(invalid_data(format!("invalid length flag:{}", flag))),
+            };
+            column.comment = match read_u8(r)? {
+                0 => None,
+                1 => Some(String::from_utf8(read_bytes(r)?).map_err(invalid_data)?),
+                flag => return Err(invalid_data(format!("invalid comment flag:{}", flag))),
+            };
+        }
+        columns.push(column);
+    }
+
+    let row_len = u64::from_le_bytes(read_array(r)?) as usize;
+    let mut data = Vec::with_capacity(row_len.min(MAX_PREALLOC));
+    for _ in 0..row_len {
+        let mut row = Vec::with_capacity(column_len.min(MAX_PREALLOC));
+        for _ in 0..column_len {
+            let tag = read_u8(r)?;
+            let odbc_type = type_from_tag(tag)
+                .ok_or_else(|| invalid_data(format!("unknown type tag:{}", tag)))?;
+            let value = match read_u8(r)? {
+                0 => None,
+                1 => Some(BytesMut::from(read_bytes(r)?.as_slice())),
+                flag => return Err(invalid_data(format!("invalid null flag:{}", flag))),
+            };
+            row.push(OdbcColumnItem { odbc_type, value });
+        }
+        data.push(row);
+    }
+    Ok((columns, data))
+}
+
+/// Append the frame of a single row to `out`, see the module docs.
+/// A cell count or value length above `u32::MAX` is rejected like in [`write_bin`], `out` is
+/// then left as it was.
+pub fn encode_row(row: &[OdbcColumnItem], out: &mut Vec<u8>) -> io::Result<()> {
+    let start = out.len();
+    let result = encode_cells(row, out);
+    if result.is_err() {
+        out.truncate(start);
+    }
+    result
+}
+
+fn encode_cells(row: &[OdbcColumnItem], out: &mut Vec<u8>) -> io::Result<()> {
+    write_len(out, row.len())?;
+    for item in row {
+        out.push(type_tag(&item.odbc_type));
+        match &item.value {
+            Some(value) => {
+                out.push(1);
+                write_bytes(out, value)?;
+            }
+            None => {
+                out.push(0);
+                write_len(out, 0)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decode a row frame written by [`encode_row`] from the start of `input`.
+/// Returns the row and the number of bytes consumed, so frames can be read back to back.
+pub fn decode_row(input: &[u8]) -> io::Result<(Vec<OdbcColumnItem>, usize)> {
+    let mut r = input;
+    let cell_len = read_u32(&mut r)? as usize;
+    let mut row = Vec::with_capacity(cell_len.min(MAX_PREALLOC));
+    for _ in 0..cell_len {
+        let tag = read_u8(&mut r)?;
+        let odbc_type =
+            type_from_tag(tag).ok_or_else(|| invalid_data(format!("unknown type tag:{}", tag)))?;
+        let null_flag = read_u8(&mut r)?;
+        let value = read_bytes(&mut r)?;
+        let value = match null_flag {
+            0 if value.is_empty() => None,
+            0 => return Err(invalid_data("NULL cell with a value")),
+            1 => Some(BytesMut::from(value.as_slice())),
+            flag => return Err(invalid_data(format!("invalid null flag:{}", flag))),
+        };
+        row.push(OdbcColumnItem { odbc_type, value });
+    }
+    Ok((row, input.len() - r.len()))
+}
+
+fn type_tag(odbc_type: &OdbcColumnType) -> u8 {
+    match odbc_type {
+        OdbcColumnType::Text => 0,
+        OdbcColumnType::WText => 1,
+        OdbcColumnType::Binary => 2,
+        OdbcColumnType::Date => 3,
+        OdbcColumnType::Time => 4,
+        OdbcColumnType::Timestamp => 5,
+        OdbcColumnType::F64 => 6,
+        OdbcColumnType::F32 => 7,
+        OdbcColumnType::I8 => 8,
+        OdbcColumnType::I16 => 9,
+        OdbcColumnType::I32 => 10,
+        OdbcColumnType::I64 => 11,
+        OdbcColumnType::U8 => 12,
+        OdbcColumnType::Bit => 13,
+        OdbcColumnType::Array => 14,
+        OdbcColumnType::Json => 15,
+        OdbcColumnType::TimestampTz => 16,
+        OdbcColumnType::Interval => 17,
+        OdbcColumnType::U64 => 18,
+    }
+}
+
+fn type_from_tag(tag: u8) -> Option<OdbcColumnType> {
+    let odbc_type = match tag {
+        0 => OdbcColumnType::Text,
+        1 => OdbcColumnType::WText,
+        2 => OdbcColumnType::Binary,
+        3 => OdbcColumnType::Date,
+        4 => OdbcColumnType::Time,
+        5 => OdbcColumnType::Timestamp,
+        6 => OdbcColumnType::F64,
+        7 => OdbcColumnType::F32,
+        8 => OdbcColumnType::I8,
+        9 => OdbcColumnType::I16,
+        10 => OdbcColumnType::I32,
+        11 => OdbcColumnType::I64,
+        12 => OdbcColumnType::U8,
+        13 => OdbcColumnType::Bit,
+        14 => OdbcColumnType::Array,
+        15 => OdbcColumnType::Json,
+        16 => OdbcColumnType::TimestampTz,
+        17 => OdbcColumnType::Interval,
+        18 => OdbcColumnType::U64,
+        _ => return None,
+    };
+    Some(odbc_type)
+}
+
+fn opt_bool_tag(value: Option<bool>) -> u8 {
+    match value {
+        None => 0,
+        Some(false) => 1,
+        Some(true) => 2,
+    }
+}
+
+fn read_opt_bool<R: Read>(r: &mut R) -> io::Result<Option<bool>> {
+    match read_u8(r)? {
+        0 => Ok(None),
+        1 => Ok(Some(false)),
+        2 => Ok(Some(true)),
+        tag => Err(invalid_data(format!("invalid optional bool:{}", tag))),
+    }
+}
+
+fn write_len<W: Write>(w: &mut W, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(invalid_data)?;
+    w.write_all(&len.to_le_bytes())
+}
+
+fn write_bytes<W: Write>(w: &mut W, bytes: &[u8]) -> io::Result<()> {
+    write_len(w, bytes.len())?;
+    w.write_all(bytes)
+}
+
+fn read_array<R: Read, const N: usize>(r: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_u8<R: Read>(r: &mut R) -> io::Result<u8> {
+    Ok(read_array::<_, 1>(r)?[0])
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(read_array(r)?))
+}
+
+fn read_bytes<R: Read>(r: &mut R) -> io::Result<Vec<u8>> {
+    let len = read_u32(r)? as usize;
+    let mut buf = Vec::with_capacity(len.min(MAX_PREALLOC));
+    r.take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(buf)
+}
+
+fn invalid_data<E>(err: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures;
+
+    #[test]
+    fn test_round_trip() {
+        let mut id = OdbcColumn::new("id".to_string(), DataType::Integer, false)
+            .with_comment("primary key, 主键");
+        id.auto_increment = Some(true);
+        id.unsigned = Some(true);
+        let mut flags = OdbcColumn::new("flags".to_string(), DataType::Binary { length: 1 }, true);
+        flags.auto_increment = Some(false);
+        flags.declared_length = Some(5);
+        let columns = vec![
+            id,
+            OdbcColumn::new(
+                "price".to_string(),
+                DataType::Decimal {
+                    precision: 18,
+                    scale: 4,
+                },
+                true,
+            ),
+            OdbcColumn::new(
+                "payload".to_string(),
+                DataType::Varbinary { length: 16 },
+                true,
+            ),
+            flags,
+        ];
+        let data = vec![
+            vec![
+                fixtures::item(OdbcColumnType::I32, Some("1")),
+                fixtures::item(OdbcColumnType::Text, Some("12345678901234.5678")),
+                fixtures::bytes_item(OdbcColumnType::Binary, Some(&[0u8, 0xff, 0x10])),
+                fixtures::bytes_item(OdbcColumnType::Binary, Some(&[0x15])),
+            ],
+            vec![
+                fixtures::item(OdbcColumnType::I32, Some("2")),
+                fixtures::item(OdbcColumnType::Text, None),
+                fixtures::item(OdbcColumnType::Binary, Some("")),
+                fixtures::item(OdbcColumnType::Binary, None),
+            ],
+        ];
+
+        let mut buf = vec![];
+        write_bin(&mut buf, &columns, &data).unwrap();
+        let (read_columns, read_data) = read_bin(&mut buf.as_slice()).unwrap();
+
+        assert_eq!(read_columns.len(), 4);
+        for (expected, actual) in columns.iter().zip(read_columns.iter()) {
+            assert_eq!(expected.name, actual.name);
+            assert_eq!(expected.data_type, actual.data_type);
+            assert_eq!(expected.nullable, actual.nullable);
+            assert_eq!(expected.auto_increment, actual.auto_increment);
+            assert_eq!(expected.unsigned, actual.unsigned);
+            assert_eq!(expected.declared_length, actual.declared_length);
+            assert_eq!(expected.comment, actual.comment);
+        }
+        assert_eq!(format!("{:?}", data), format!("{:?}", read_data));
+    }
+
+    #[test]
+    fn test_read_invalid_magic() {
+        let err = read_bin(&mut &b"XXXX\x01"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // column count and name length of u32::MAX without the data behind them
+        let mut corrupt = b"OBRS\x01".to_vec();
+        corrupt.extend_from_slice(&u32::MAX.to_le_bytes());
+        corrupt.extend_from_slice(&u32::MAX.to_le_bytes());
+        corrupt.extend_from_slice(b"id");
+        let err = read_bin(&mut corrupt.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        // version 1 files are still read
+        let mut v1 = b"OBRS\x01".to_vec();
+        v1.extend_from_slice(&1u32.to_le_bytes());
+        v1.extend_from_slice(&2u32.to_le_bytes());
+        v1.extend_from_slice(b"id");
+        v1.extend_from_slice(&SqlDataType::INTEGER.0.to_le_bytes());
+        v1.extend_from_slice(&10u64.to_le_bytes());
+        v1.extend_from_slice(&0i16.to_le_bytes());
+        v1.push(0);
+        v1.extend_from_slice(&0u64.to_le_bytes());
+        let (columns, data) = read_bin(&mut v1.as_slice()).unwrap();
+        assert_eq!(columns[0].name, "id");
+        assert_eq!(columns[0].data_type, DataType::Integer);
+        assert_eq!(columns[0].comment, None);
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn test_encode_decode_row() {
+        let rows = vec![
+            vec![
+                fixtures::item(OdbcColumnType::I64, Some("42")),
+                fixtures::item(OdbcColumnType::Text, None),
+            ],
+            vec![fixtures::item(OdbcColumnType::Binary, Some(""))],
+        ];
+        let mut out = vec![];
+        for row in rows.iter() {
+            encode_row(row, &mut out).unwrap();
+        }
+
+        let (first, used) = decode_row(&out).unwrap();
+        let (second, rest) = decode_row(&out[used..]).unwrap();
+        assert_eq!(used + rest, out.len());
+        assert_eq!(vec![first, second], rows);
+        assert_eq!(
+            decode_row(&out[..used - 1]).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn test_round_trip_all_types() {
+        let (columns, data) = crate::export::fixtures::all_types();
+        let mut buf = vec![];
+        write_bin(&mut buf, &columns, &data).unwrap();
+        let (read_columns, read_data) = read_bin(&mut buf.as_slice()).unwrap();
+        assert_eq!(read_columns.len(), columns.len());
+        assert_eq!(read_data, data);
+    }
+}
@@ -0,0 +1,338 @@
+use crate::export::{CellRenderer, PrintOptions, ROW_NUMBER_HEADER};
+use crate::extension::odbc::{OdbcColumn, OdbcColumnItem};
+use std::io::{self, Write};
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`, default.
+    #[default]
+    Lf,
+    /// `\r\n`, expected by Excel on Windows.
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    /// Prepend a UTF-8 BOM so Excel detects the encoding, default is false.
+    pub write_bom: bool,
+    pub line_ending: LineEnding,
+    /// Keep NULL distinct from the empty string like Postgres `COPY ... CSV`: NULL is an unquoted
+    /// empty field whatever `print.null_text` is, an empty string is written as `""`.
+    /// [`parse_csv`] reads it back the same way. Default is false.
+    pub quote_empty: bool,
+    /// Cell rendering options.
+    pub print: PrintOptions,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: ',',
+            write_bom: false,
+            line_ending: LineEnding::default(),
+            quote_empty: false,
+            print: PrintOptions::default(),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Options for CSV files opened by Excel: UTF-8 BOM and CRLF line endings.
+    pub fn excel() -> Self {
+        CsvOptions {
+            write_bom: true,
+            line_ending: LineEnding::CrLf,
+            ..Default::default()
+        }
+    }
+}
+
+/// Same text as `print`, quoting is applied when the record is written.
+impl CellRenderer for CsvOptions {
+    fn render(&self, item: &OdbcColumnItem, column: &OdbcColumn) -> String {
+        self.print.render(item, column)
+    }
+}
+
+/// Write the result set as CSV, quoting fields per RFC 4180 when needed.
+pub fn write_csv<W: Write>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+    options: &CsvOptions,
+) -> io::Result<()> {
+    write_csv_header(w, columns, options)?;
+    write_csv_rows(w, columns, data, options)
+}
+
+/// Write the optional BOM and the header record, unless `print.write_header` is false.
+pub fn write_csv_header<W: Write>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    options: &CsvOptions,
+) -> io::Result<()> {
+    if options.write_bom {
+        w.write_all(UTF8_BOM)?;
+    }
+    if !options.print.write_header {
+        return Ok(());
+    }
+
+    let row_number = options
+        .print
+        .with_row_numbers
+        .then(|| ROW_NUMBER_HEADER.to_string());
+    let header = row_number
+        .into_iter()
+        .chain(columns.iter().map(|c| options.print.header(c)))
+        .map(Some)
+        .collect::<Vec<_>>();
+    write_record(w, &header, options)
+}
+
+/// Write data records only, to append rows after [`write_csv_header`].
+pub fn write_csv_rows<W: Write>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+    options: &CsvOptions,
+) -> io::Result<()> {
+    write_csv_rows_at(w, columns, data, 1, options)
+}
+
+/// [`write_csv_rows`] for rows following others, `first_row` is the number of the first row
+/// when `print.with_row_numbers` is set.
+pub fn write_csv_rows_at<W: Write>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+    first_row: usize,
+    options: &CsvOptions,
+) -> io::Result<()> {
+    for (index, row) in data.iter().enumerate() {
+        let row_number = options
+            .print
+            .with_row_numbers
+            .then(|| (first_row + index).to_string());
+        let record = row_number
+            .into_iter()
+            .map(Some)
+            .chain(row.iter().zip(columns.iter()).map(|(item, column)| {
+                (!(options.quote_empty && item.is_null())).then(|| options.render(item, column))
+            }))
+            .collect::<Vec<_>>();
+        write_record(w, &record, options)?;
+    }
+    Ok(())
+}
+
+/// `None` fields are NULL with `quote_empty`, written as nothing.
+fn write_record<W: Write>(
+    w: &mut W,
+    fields: &[Option<String>],
+    options: &CsvOptions,
+) -> io::Result<()> {
+    let mut delimiter = [0u8; 4];
+    let delimiter = options.delimiter.encode_utf8(&mut delimiter);
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            w.write_all(delimiter.as_bytes())?;
+        }
+        if let Some(field) = field {
+            w.write_all(escape_field(field, options).as_bytes())?;
+        }
+    }
+    w.write_all(options.line_ending.as_str().as_bytes())
+}
+
+fn escape_field(field: &str, options: &CsvOptions) -> String {
+    if (options.quote_empty && field.is_empty())
+        || field.contains([options.delimiter, '"', '\n', '\r'])
+    {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Parse CSV `text` written with `options` into records, the header record included.
+///
+/// Fields may be quoted per RFC 4180 and span lines, a leading UTF-8 BOM is skipped and both line
+/// endings are accepted. With `quote_empty` an unquoted empty field is `None` (NULL) and `""` is
+/// the empty string, otherwise every field is `Some`.
+pub fn parse_csv(text: &str, options: &CsvOptions) -> io::Result<Vec<Vec<Option<String>>>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    let end_field = |record: &mut Vec<Option<String>>, field: &mut String, quoted: &mut bool| {
+        let value = std::mem::take(field);
+        record.push((*quoted || !options.quote_empty || !value.is_empty()).then_some(value));
+        *quoted = false;
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() && !quoted => {
+                quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => field.push(c),
+                        None => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!(
+                                    "unterminated quoted field in record {}",
+                                    records.len() + 1
+                                ),
+                            ))
+                        }
+                    }
+                }
+            }
+            c if c == options.delimiter => end_field(&mut record, &mut field, &mut quoted),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                end_field(&mut record, &mut field, &mut quoted);
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || quoted || !record.is_empty() {
+        end_field(&mut record, &mut field, &mut quoted);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures;
+    use crate::extension::odbc::OdbcColumnType;
+    use crate::odbc_api::DataType;
+
+    fn fixture() -> (Vec<OdbcColumn>, Vec<Vec<OdbcColumnItem>>) {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, false),
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 20 }, true),
+        ];
+        let data = vec![vec![
+            fixtures::item(OdbcColumnType::I32, Some("1")),
+            fixtures::item(OdbcColumnType::Text, Some("a,\"b\"")),
+        ]];
+        (columns, data)
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let (columns, data) = fixture();
+        let mut buf = vec![];
+        write_csv(&mut buf, &columns, &data, &CsvOptions::default()).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "id,name\n1,\"a,\"\"b\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_write_csv_excel() {
+        let (columns, data) = fixture();
+        let mut buf = vec![];
+        write_csv(&mut buf, &columns, &data, &CsvOptions::excel()).unwrap();
+        assert!(buf.starts_with(UTF8_BOM));
+        let text = String::from_utf8(buf[UTF8_BOM.len()..].to_vec()).unwrap();
+        assert_eq!(text, "id,name\r\n1,\"a,\"\"b\"\"\"\r\n");
+    }
+
+    #[test]
+    fn test_write_csv_row_numbers() {
+        let (columns, data) = fixture();
+        let mut options = CsvOptions::default();
+        options.print.with_row_numbers = true;
+        let mut buf = vec![];
+        write_csv_header(&mut buf, &columns, &options).unwrap();
+        write_csv_rows_at(&mut buf, &columns, &data, 1, &options).unwrap();
+        write_csv_rows_at(&mut buf, &columns, &data, 2, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "#,id,name\n1,1,\"a,\"\"b\"\"\"\n2,1,\"a,\"\"b\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_write_csv_all_types() {
+        let (columns, data) = crate::export::fixtures::all_types();
+        let mut buf = vec![];
+        write_csv(&mut buf, &columns, &data, &CsvOptions::default()).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let header = columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>();
+        assert!(text.starts_with(&format!("{}\n", header.join(","))));
+        // the NULL row
+        assert!(text.contains(&format!("\n{}\n", ",".repeat(columns.len() - 1))));
+        assert!(text.contains("\"h\u{e9}llo \u{4e16}\u{754c}, \"\"quoted\"\"\n\""));
+    }
+
+    #[test]
+    fn test_write_csv_without_header() {
+        let (columns, data) = fixture();
+        let mut options = CsvOptions::default();
+        options.print.write_header = false;
+        let mut buf = vec![];
+        write_csv(&mut buf, &columns, &data, &options).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "1,\"a,\"\"b\"\"\"\n");
+    }
+
+    #[test]
+    fn test_quote_empty_round_trip() {
+        let columns = vec![OdbcColumn::new(
+            "name".to_string(),
+            DataType::Varchar { length: 20 },
+            true,
+        )];
+        let item = |v: Option<&str>| vec![fixtures::item(OdbcColumnType::Text, v)];
+        let data = vec![item(None), item(Some("")), item(Some("a\"b"))];
+        let options = CsvOptions {
+            quote_empty: true,
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        write_csv(&mut buf, &columns, &data, &options).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert_eq!(text, "name\n\n\"\"\n\"a\"\"b\"\n");
+
+        let records = parse_csv(&text, &options).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec![Some("name".to_string())],
+                vec![None],
+                vec![Some("".to_string())],
+                vec![Some("a\"b".to_string())],
+            ]
+        );
+        let records = parse_csv(&text, &CsvOptions::default()).unwrap();
+        assert_eq!(records[1], vec![Some("".to_string())]);
+        assert!(parse_csv("\"open", &options).is_err());
+    }
+}
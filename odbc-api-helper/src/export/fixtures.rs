@@ -0,0 +1,159 @@
+//! Deterministic result sets for exporter tests, one column per `OdbcColumnType`.
+//!
+//! [`all_types`] has three rows: plain values, all NULLs, and edge cases (empty text, non-ASCII,
+//! binary with NUL and high bytes, extreme numbers), the cases that tend to regress.
+use crate::extension::odbc::{OdbcColumn, OdbcColumnItem, OdbcColumnType};
+use crate::odbc_api::{sys::SqlDataType, DataType};
+use bytes::BytesMut;
+
+/// `(name, source type, cell type)` of every fixture column.
+fn schema() -> Vec<(&'static str, DataType, OdbcColumnType)> {
+    vec![
+        (
+            "text",
+            DataType::Varchar { length: 20 },
+            OdbcColumnType::Text,
+        ),
+        (
+            "wtext",
+            DataType::WVarchar { length: 20 },
+            OdbcColumnType::WText,
+        ),
+        (
+            "binary",
+            DataType::Varbinary { length: 8 },
+            OdbcColumnType::Binary,
+        ),
+        ("date", DataType::Date, OdbcColumnType::Date),
+        (
+            "time",
+            DataType::Time { precision: 0 },
+            OdbcColumnType::Time,
+        ),
+        (
+            "timestamp",
+            DataType::Timestamp { precision: 3 },
+            OdbcColumnType::Timestamp,
+        ),
+        ("f64", DataType::Double, OdbcColumnType::F64),
+        ("f32", DataType::Real, OdbcColumnType::F32),
+        ("i8", DataType::TinyInt, OdbcColumnType::I8),
+        ("i16", DataType::SmallInt, OdbcColumnType::I16),
+        ("i32", DataType::Integer, OdbcColumnType::I32),
+        ("i64", DataType::BigInt, OdbcColumnType::I64),
+        ("u8", DataType::TinyInt, OdbcColumnType::U8),
+        ("bit", DataType::Bit, OdbcColumnType::Bit),
+        (
+            "array",
+            DataType::Varchar { length: 100 },
+            OdbcColumnType::Array,
+        ),
+        (
+            "json",
+            DataType::LongVarchar { length: 0 },
+            OdbcColumnType::Json,
+        ),
+        (
+            "timestamp_tz",
+            DataType::Other {
+                data_type: SqlDataType(-155),
+                column_size: 34,
+                decimal_digits: 7,
+            },
+            OdbcColumnType::TimestampTz,
+        ),
+        (
+            "interval",
+            DataType::Other {
+                data_type: SqlDataType(110),
+                column_size: 0,
+                decimal_digits: 0,
+            },
+            OdbcColumnType::Interval,
+        ),
+        ("u64", DataType::BigInt, OdbcColumnType::U64),
+    ]
+}
+
+/// A single cell, NULL when `value` is `None`.
+pub(crate) fn item(odbc_type: OdbcColumnType, value: Option<&str>) -> OdbcColumnItem {
+    OdbcColumnItem {
+        odbc_type,
+        value: value.map(BytesMut::from),
+    }
+}
+
+/// [`item`] for cells that are not valid UTF-8.
+pub(crate) fn bytes_item(odbc_type: OdbcColumnType, value: Option<&[u8]>) -> OdbcColumnItem {
+    OdbcColumnItem {
+        odbc_type,
+        value: value.map(BytesMut::from),
+    }
+}
+
+/// Columns and rows covering every `OdbcColumnType`, see the module docs.
+pub(crate) fn all_types() -> (Vec<OdbcColumn>, Vec<Vec<OdbcColumnItem>>) {
+    let schema = schema();
+    let columns = schema
+        .iter()
+        .map(|(name, data_type, _)| OdbcColumn::new(name.to_string(), *data_type, true))
+        .collect();
+
+    let values: [&[u8]; 19] = [
+        b"plain",
+        b"wide",
+        &[0x01, 0x02],
+        b"2022-01-02",
+        b"3:04:05",
+        b"2022-01-02 3:04:05.5",
+        b"1.5",
+        b"-2.25",
+        b"-8",
+        b"16",
+        b"32",
+        b"64",
+        &[8],
+        b"true",
+        b"{1,2,NULL}",
+        br#"{"k":[1,2]}"#,
+        b"2022-08-24 15:50:36.5+08:00",
+        b"P1DT2H",
+        b"42",
+    ];
+    let edges: [&[u8]; 19] = [
+        b"",
+        "h\u{e9}llo \u{4e16}\u{754c}, \"quoted\"\n".as_bytes(),
+        &[0x00, 0xff, 0x22, 0x2c],
+        b"1970-01-01",
+        b"23:59:59",
+        b"1970-01-01 0:00:00.0",
+        b"-0.0001",
+        b"3.4028235e38",
+        b"-128",
+        b"-32768",
+        b"2147483647",
+        b"-9223372036854775808",
+        &[255],
+        b"false",
+        br#"{"a,b","say \"hi\""}"#,
+        "{\"\u{e9}\":\"\"}".as_bytes(),
+        b"1970-01-01 0:00:00.0-12:00",
+        b"PT0S",
+        b"18446744073709551615",
+    ];
+
+    let row = |cells: Option<&[&[u8]; 19]>| {
+        schema
+            .iter()
+            .enumerate()
+            .map(|(index, (_, _, odbc_type))| OdbcColumnItem {
+                odbc_type: *odbc_type,
+                value: cells.map(|cells| BytesMut::from(cells[index])),
+            })
+            .collect::<Vec<_>>()
+    };
+    (
+        columns,
+        vec![row(Some(&values)), row(None), row(Some(&edges))],
+    )
+}
@@ -0,0 +1,110 @@
+use crate::export::{CellRenderer, PrintOptions, ROW_NUMBER_HEADER};
+use crate::extension::odbc::{OdbcColumn, OdbcColumnItem, OdbcColumnType};
+
+#[derive(Debug, Clone, Default)]
+pub struct HtmlOptions {
+    /// Cell rendering options, NULL cells use `print.null_text`.
+    pub print: PrintOptions,
+}
+
+/// Same text as `print`, escaping is applied when the table is written.
+impl CellRenderer for HtmlOptions {
+    fn render(&self, item: &OdbcColumnItem, column: &OdbcColumn) -> String {
+        self.print.render(item, column)
+    }
+}
+
+/// Render the result set as an HTML `<table>`, e.g. for emailing reports.
+///
+/// Header and cell text is HTML-escaped, the `<thead>` is left out when `print.write_header` is
+/// false. Every `<td>` gets the lowercase column type as `class`,
+/// e.g. `i64` or `text`, numeric cells additionally get `numeric`.
+pub fn print_html(
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+    options: &HtmlOptions,
+) -> String {
+    let mut html = String::from("<table>\n");
+    if options.print.write_header {
+        html.push_str("<thead>\n<tr>");
+        if options.print.with_row_numbers {
+            html.push_str(&format!("<th>{}</th>", ROW_NUMBER_HEADER));
+        }
+        for column in columns {
+            html.push_str("<th>");
+            html.push_str(&escape_html(&options.print.header(column)));
+            html.push_str("</th>");
+        }
+        html.push_str("</tr>\n</thead>\n");
+    }
+    html.push_str("<tbody>\n");
+
+    for (index, row) in data.iter().enumerate() {
+        html.push_str("<tr>");
+        if options.print.with_row_numbers {
+            html.push_str(&format!("<td class=\"row-number\">{}</td>", index + 1));
+        }
+        for (item, column) in row.iter().zip(columns.iter()) {
+            html.push_str(&format!(
+                "<td class=\"{}\">{}</td>",
+                type_class(item.odbc_type),
+                escape_html(&options.render(item, column))
+            ));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+fn type_class(odbc_type: OdbcColumnType) -> String {
+    let class = format!("{:?}", odbc_type).to_lowercase();
+    if odbc_type.is_numeric() {
+        format!("{} numeric", class)
+    } else {
+        class
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures;
+    use crate::odbc_api::DataType;
+
+    #[test]
+    fn test_print_html() {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::BigInt, false),
+            OdbcColumn::new("<note>".to_string(), DataType::Varchar { length: 20 }, true),
+        ];
+        let data = vec![vec![
+            fixtures::item(OdbcColumnType::I64, Some("7")),
+            fixtures::item(OdbcColumnType::Text, None),
+        ]];
+        let mut options = HtmlOptions::default();
+        options.print.null_text = "<null>".to_string();
+
+        assert_eq!(
+            print_html(&columns, &data, &options),
+            "<table>\n<thead>\n<tr><th>id</th><th>&lt;note&gt;</th></tr>\n</thead>\n<tbody>\n\
+             <tr><td class=\"i64 numeric\">7</td><td class=\"text\">&lt;null&gt;</td></tr>\n\
+             </tbody>\n</table>\n"
+        );
+    }
+}
@@ -0,0 +1,451 @@
+use crate::export::{CellRenderer, PrintOptions};
+use crate::extension::odbc::{type_display, OdbcColumn, OdbcColumnItem, OdbcColumnType};
+use serde_json::{Number, Value};
+use std::io::{self, Write};
+
+/// Document shape written by [`write_json`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonLayout {
+    /// `[{"id":1,...},...]`, default.
+    #[default]
+    Records,
+    /// Self-describing `{"columns":[{"name":"id","type":"INTEGER"},...],"rows":[[1,...],...]}`,
+    /// column types are given by `type_display`, columns with a comment also have `"comment"`.
+    Envelope,
+    /// Column-oriented `{"id":[1,2,...],"name":["a",...]}`, e.g. for plotting libraries.
+    Columns,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JsonOptions {
+    /// Cell rendering options, used for the cells written as JSON strings.
+    pub print: PrintOptions,
+    /// String written for `NaN`/`Infinity` float cells, which JSON numbers can't express.
+    /// `None` (default) writes `null`.
+    pub non_finite_text: Option<String>,
+    /// Shape of the document written by [`write_json`], `write_json_lines` always writes records.
+    pub layout: JsonLayout,
+}
+
+/// The text of the value written by [`cell_value`], unquoted. NULL uses `print.null_text`.
+impl CellRenderer for JsonOptions {
+    fn render(&self, item: &OdbcColumnItem, column: &OdbcColumn) -> String {
+        match cell_value(item, column, self) {
+            Ok(Value::String(s)) => s,
+            Ok(Value::Null) | Err(_) => self.print.render(item, column),
+            Ok(v) => v.to_string(),
+        }
+    }
+}
+
+/// Write the result set as a JSON array of objects, keys in column order, as an envelope with
+/// the column types or as one array per column, see [`JsonLayout`].
+///
+/// NULL is written as `null`, numbers as JSON numbers, `Bit` (and `treat_as_bool` columns) as
+/// booleans and `Json` cells are inlined as-is, redacted columns are always strings. Non-finite
+/// floats are written as `null` or `non_finite_text`. Everything else is a string rendered by
+/// `print`.
+///
+/// Keys follow `print.header_transform`.
+pub fn write_json<W: Write>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+    options: &JsonOptions,
+) -> io::Result<()> {
+    match options.layout {
+        JsonLayout::Records => {}
+        JsonLayout::Envelope => return write_envelope(w, columns, data, options),
+        JsonLayout::Columns => return write_columns(w, columns, data, options),
+    }
+    w.write_all(b"[")?;
+    for (index, row) in data.iter().enumerate() {
+        if index > 0 {
+            w.write_all(b",")?;
+        }
+        write_record(w, columns, row, options)?;
+    }
+    w.write_all(b"]")
+}
+
+/// Write the result set as JSON lines, one object per row followed by `\n`.
+/// Values are written as described in [`write_json`].
+pub fn write_json_lines<W: Write>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+    options: &JsonOptions,
+) -> io::Result<()> {
+    for row in data {
+        write_record(w, columns, row, options)?;
+        w.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Write rows from an iterator as a single JSON array of records, one record at a time, so the
+/// result is never held in memory. Returns the number of rows written, no rows write `[]`.
+/// Values are written as described in [`write_json`], `layout` is ignored.
+pub fn stream_json_array<W, I, R>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    rows: I,
+    options: &JsonOptions,
+) -> io::Result<usize>
+where
+    W: Write,
+    I: IntoIterator<Item = R>,
+    R: AsRef<[OdbcColumnItem]>,
+{
+    w.write_all(b"[")?;
+    let mut written = 0;
+    for row in rows {
+        if written > 0 {
+            w.write_all(b",")?;
+        }
+        write_record(w, columns, row.as_ref(), options)?;
+        written += 1;
+    }
+    w.write_all(b"]")?;
+    Ok(written)
+}
+
+fn write_envelope<W: Write>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+    options: &JsonOptions,
+) -> io::Result<()> {
+    w.write_all(b"{\"columns\":[")?;
+    for (index, column) in columns.iter().enumerate() {
+        if index > 0 {
+            w.write_all(b",")?;
+        }
+        w.write_all(b"{\"name\":")?;
+        serde_json::to_writer(&mut *w, &options.print.header(column))?;
+        w.write_all(b",\"type\":")?;
+        serde_json::to_writer(&mut *w, &type_display(&column.data_type))?;
+        if let Some(comment) = &column.comment {
+            w.write_all(b",\"comment\":")?;
+            serde_json::to_writer(&mut *w, comment)?;
+        }
+        w.write_all(b"}")?;
+    }
+    w.write_all(b"],\"rows\":[")?;
+    for (index, row) in data.iter().enumerate() {
+        if index > 0 {
+            w.write_all(b",")?;
+        }
+        let values = row
+            .iter()
+            .zip(columns.iter())
+            .map(|(item, column)| cell_value(item, column, options))
+            .collect::<io::Result<Vec<_>>>()?;
+        serde_json::to_writer(&mut *w, &values)?;
+    }
+    w.write_all(b"]}")
+}
+
+fn write_columns<W: Write>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+    options: &JsonOptions,
+) -> io::Result<()> {
+    w.write_all(b"{")?;
+    for (index, column) in columns.iter().enumerate() {
+        if index > 0 {
+            w.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut *w, &options.print.header(column))?;
+        w.write_all(b":")?;
+        let values = data
+            .iter()
+            .filter_map(|row| row.get(index))
+            .map(|item| cell_value(item, column, options))
+            .collect::<io::Result<Vec<_>>>()?;
+        serde_json::to_writer(&mut *w, &values)?;
+    }
+    w.write_all(b"}")
+}
+
+fn write_record<W: Write>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    row: &[OdbcColumnItem],
+    options: &JsonOptions,
+) -> io::Result<()> {
+    w.write_all(b"{")?;
+    for (index, (item, column)) in row.iter().zip(columns.iter()).enumerate() {
+        if index > 0 {
+            w.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut *w, &options.print.header(column))?;
+        w.write_all(b":")?;
+        serde_json::to_writer(&mut *w, &cell_value(item, column, options)?)?;
+    }
+    w.write_all(b"}")
+}
+
+/// The JSON value of a single cell.
+pub fn cell_value(
+    item: &OdbcColumnItem,
+    column: &OdbcColumn,
+    options: &JsonOptions,
+) -> io::Result<Value> {
+    let value = match &item.value {
+        Some(value) => value,
+        None => return Ok(Value::Null),
+    };
+    let invalid = |e: serde_json::Error| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("column `{}` value is not valid json:{}", column.name, e),
+        )
+    };
+
+    if options.print.redact.contains_key(&column.name) {
+        return Ok(Value::String(options.print.render(item, column)));
+    }
+
+    let value = match item.odbc_type {
+        OdbcColumnType::Json => serde_json::from_slice(value).map_err(invalid)?,
+        OdbcColumnType::Bit => Value::Bool(value.as_ref() == b"true"),
+        OdbcColumnType::U8 => {
+            let v = value.first().copied().unwrap_or_default();
+            if options.print.treat_as_bool.contains(&column.name) {
+                Value::Bool(v != 0)
+            } else {
+                Value::Number(v.into())
+            }
+        }
+        OdbcColumnType::F32 | OdbcColumnType::F64
+            if String::from_utf8_lossy(value)
+                .parse::<f64>()
+                .is_ok_and(|v| !v.is_finite()) =>
+        {
+            match &options.non_finite_text {
+                Some(text) => Value::String(text.clone()),
+                None => Value::Null,
+            }
+        }
+        OdbcColumnType::I8
+        | OdbcColumnType::I16
+        | OdbcColumnType::I32
+        | OdbcColumnType::I64
+        | OdbcColumnType::U64
+        | OdbcColumnType::F32
+        | OdbcColumnType::F64 => {
+            let number = String::from_utf8_lossy(value)
+                .parse::<Number>()
+                .map_err(invalid)?;
+            if options.print.treat_as_bool.contains(&column.name) && number.is_i64() {
+                Value::Bool(number.as_i64() != Some(0))
+            } else {
+                Value::Number(number)
+            }
+        }
+        _ => Value::String(options.print.render(item, column)),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::query::QueryResult;
+    use crate::export::fixtures::{self, item};
+    use crate::odbc_api::DataType;
+
+    #[test]
+    fn test_write_json() {
+        let mut result = QueryResult {
+            columns: vec![
+                OdbcColumn::new("id".to_string(), DataType::Integer, false),
+                OdbcColumn::new("name".to_string(), DataType::Varchar { length: 20 }, true),
+                OdbcColumn::new(
+                    "attrs".to_string(),
+                    DataType::LongVarchar { length: 0 },
+                    true,
+                ),
+                OdbcColumn::new("ok".to_string(), DataType::Bit, true),
+            ],
+            data: vec![
+                vec![
+                    item(OdbcColumnType::I32, Some("1")),
+                    item(OdbcColumnType::Text, Some("a\"b")),
+                    item(OdbcColumnType::Text, Some(r#"{"k":[1,2]}"#)),
+                    item(OdbcColumnType::Bit, Some("true")),
+                ],
+                vec![
+                    item(OdbcColumnType::I32, Some("2")),
+                    item(OdbcColumnType::Text, None),
+                    item(OdbcColumnType::Text, Some("not json")),
+                    item(OdbcColumnType::Bit, None),
+                ],
+            ],
+        };
+        result.tag_json_columns(&["attrs"]);
+        assert_eq!(result.data[0][2].odbc_type, OdbcColumnType::Json);
+        assert_eq!(result.data[1][2].odbc_type, OdbcColumnType::Text);
+
+        let mut buf = vec![];
+        write_json(
+            &mut buf,
+            &result.columns,
+            &result.data,
+            &JsonOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            r#"[{"id":1,"name":"a\"b","attrs":{"k":[1,2]},"ok":true},{"id":2,"name":null,"attrs":"not json","ok":null}]"#
+        );
+    }
+
+    #[test]
+    fn test_write_json_lines() {
+        let columns = vec![OdbcColumn::new("id".to_string(), DataType::Integer, false)];
+        let data = vec![
+            vec![fixtures::item(OdbcColumnType::I32, Some("1"))],
+            vec![fixtures::item(OdbcColumnType::I32, None)],
+        ];
+        let mut buf = vec![];
+        write_json_lines(&mut buf, &columns, &data, &JsonOptions::default()).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "{\"id\":1}\n{\"id\":null}\n"
+        );
+    }
+
+    #[test]
+    fn test_write_json_non_finite() {
+        let columns = vec![OdbcColumn::new("v".to_string(), DataType::Double, true)];
+        let data = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1.5]
+            .iter()
+            .map(|v| {
+                vec![fixtures::item(
+                    OdbcColumnType::F64,
+                    Some(v.to_string().as_str()),
+                )]
+            })
+            .collect::<Vec<_>>();
+
+        let mut buf = vec![];
+        write_json(&mut buf, &columns, &data, &JsonOptions::default()).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            r#"[{"v":null},{"v":null},{"v":null},{"v":1.5}]"#
+        );
+
+        let options = JsonOptions {
+            non_finite_text: Some("NaN".to_string()),
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        write_json(&mut buf, &columns, &data, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            r#"[{"v":"NaN"},{"v":"NaN"},{"v":"NaN"},{"v":1.5}]"#
+        );
+    }
+
+    #[test]
+    fn test_write_json_envelope() {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, false),
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 20 }, true),
+        ];
+        let data = vec![vec![
+            fixtures::item(OdbcColumnType::I32, Some("1")),
+            fixtures::item(OdbcColumnType::Text, None),
+        ]];
+        let options = JsonOptions {
+            layout: JsonLayout::Envelope,
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        write_json(&mut buf, &columns, &data, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            r#"{"columns":[{"name":"id","type":"INTEGER"},{"name":"name","type":"VARCHAR(20)"}],"rows":[[1,null]]}"#
+        );
+
+        let columns = vec![columns[0].clone().with_comment("user \"id\"")];
+        let mut buf = vec![];
+        write_json(&mut buf, &columns, &[], &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            r#"{"columns":[{"name":"id","type":"INTEGER","comment":"user \"id\""}],"rows":[]}"#
+        );
+    }
+
+    #[test]
+    fn test_write_json_all_types() {
+        let (columns, data) = crate::export::fixtures::all_types();
+        let mut buf = vec![];
+        write_json(&mut buf, &columns, &data, &JsonOptions::default()).unwrap();
+        let rows: Vec<serde_json::Map<String, Value>> = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[1].values().all(Value::is_null));
+        assert_eq!(rows[0]["json"], serde_json::json!({"k": [1, 2]}));
+        assert_eq!(rows[2]["text"], "");
+        assert_eq!(rows[2]["i64"], i64::MIN);
+        assert_eq!(rows[2]["u8"], 255);
+    }
+
+    #[test]
+    fn test_stream_json_array() {
+        let columns = vec![OdbcColumn::new("id".to_string(), DataType::Integer, false)];
+        let row = |v: &str| vec![fixtures::item(OdbcColumnType::I32, Some(v))];
+        let options = JsonOptions::default();
+
+        let mut buf = vec![];
+        let rows = ["1", "2", "3"].into_iter().map(row);
+        assert_eq!(
+            stream_json_array(&mut buf, &columns, rows, &options).unwrap(),
+            3
+        );
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            r#"[{"id":1},{"id":2},{"id":3}]"#
+        );
+
+        let mut buf = vec![];
+        let rows: Vec<Vec<OdbcColumnItem>> = vec![];
+        assert_eq!(
+            stream_json_array(&mut buf, &columns, &rows, &options).unwrap(),
+            0
+        );
+        assert_eq!(buf, b"[]");
+    }
+
+    #[test]
+    fn test_write_json_columns() {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, false),
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 20 }, true),
+        ];
+        let row = |id: &str, name: Option<&str>| {
+            vec![
+                fixtures::item(OdbcColumnType::I32, Some(id)),
+                fixtures::item(OdbcColumnType::Text, name),
+            ]
+        };
+        let options = JsonOptions {
+            layout: JsonLayout::Columns,
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        let data = vec![row("1", Some("a")), row("2", None)];
+        write_json(&mut buf, &columns, &data, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            r#"{"id":[1,2],"name":["a",null]}"#
+        );
+
+        let mut buf = vec![];
+        write_json(&mut buf, &columns, &[], &options).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), r#"{"id":[],"name":[]}"#);
+    }
+}
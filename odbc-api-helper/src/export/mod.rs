@@ -0,0 +1,733 @@
+pub mod aggregate;
+pub mod binary;
+pub mod csv;
+#[cfg(test)]
+pub(crate) mod fixtures;
+pub mod html;
+pub mod json;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+pub mod split;
+pub mod stream;
+
+use crate::extension::interval::Interval;
+use crate::extension::odbc::{fraction_digits, OdbcColumn, OdbcColumnItem, OdbcColumnType};
+use crate::extension::util::{parse_to_data_time, parse_to_time};
+use chrono::Timelike;
+use odbc_common::print_table::display_width;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Once};
+
+/// Renderer used for the printed table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TableRenderer {
+    /// nu_table with rounded borders and width computation, default.
+    #[default]
+    Nu,
+    /// Fixed padding from a single pass, for huge tables or constrained terminals.
+    /// See [`odbc_common::print_table::simple_table_string`].
+    Simple,
+}
+
+/// Case transform applied to column names in the exporter headers and JSON keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeaderCase {
+    /// Keep the name as reported by the driver, default.
+    #[default]
+    AsIs,
+    Lower,
+    Upper,
+    /// `user_id`
+    Snake,
+    /// `userId`
+    Camel,
+}
+
+impl HeaderCase {
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            HeaderCase::AsIs => name.to_string(),
+            HeaderCase::Lower => name.to_lowercase(),
+            HeaderCase::Upper => name.to_uppercase(),
+            HeaderCase::Snake => split_words(name)
+                .iter()
+                .map(|w| w.to_lowercase())
+                .collect::<Vec<_>>()
+                .join("_"),
+            HeaderCase::Camel => split_words(name)
+                .iter()
+                .enumerate()
+                .map(|(index, w)| {
+                    let w = w.to_lowercase();
+                    if index == 0 {
+                        return w;
+                    }
+                    let mut chars = w.chars();
+                    match chars.next() {
+                        Some(first) => first.to_uppercase().chain(chars).collect(),
+                        None => w,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Split a column name into words on non-alphanumeric separators and case changes,
+/// e.g. `UserID`, `user_id` and `HTTPServer name` give `User ID`, `user id` and `HTTP Server name`.
+fn split_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (index, c) in chars.iter().copied().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !word.is_empty() {
+            let prev = chars[index - 1];
+            let next_lower = chars.get(index + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// How `Time` and `Interval` cells are displayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeRendering {
+    /// The stored text, `01:30:00` or ISO-8601 `PT1H30M`, default.
+    #[default]
+    Clock,
+    /// Explicit units, e.g. `1h 30m`, see [`Interval::human_units`].
+    HumanUnits,
+}
+
+/// How a redacted column is masked, see [`PrintOptions::redact`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedactStyle {
+    /// Replace every value with a fixed text.
+    Mask(String),
+    /// Replace the value with the first 16 hex digits of SHA-256 over `salt` followed by the
+    /// value. Equal values give equal hashes, across releases too, so joins and distinct counts
+    /// still work. Keep `salt` secret, without it low-entropy values such as phone numbers can't
+    /// be recovered by hashing every candidate.
+    Hash { salt: String },
+    /// One `*` per character, preserving the length.
+    Stars,
+}
+
+impl RedactStyle {
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            RedactStyle::Mask(mask) => mask.clone(),
+            RedactStyle::Hash { salt } => {
+                let mut hasher = Sha256::new();
+                hasher.update(salt.as_bytes());
+                hasher.update(text.as_bytes());
+                hasher.finalize()[..8]
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect()
+            }
+            RedactStyle::Stars => "*".repeat(text.chars().count()),
+        }
+    }
+}
+
+/// Custom formatter for the cells of one column, see [`PrintOptions::add_formatter`].
+pub type CellFormatter = Arc<dyn Fn(&OdbcColumnItem) -> String + Send + Sync>;
+
+/// Rendering options shared by the table printer and the exporters.
+/// Only the display changes, the underlying `OdbcColumnItem` data is never mutated.
+#[derive(Clone)]
+pub struct PrintOptions {
+    /// Text used for NULL cells, default is empty.
+    pub null_text: String,
+    /// Text used for a true `Bit` value, default is `true`.
+    pub bit_true: String,
+    /// Text used for a false `Bit` value, default is `false`.
+    pub bit_false: String,
+    /// Integer columns (matched by column name) that are semantically boolean, e.g. `TINYINT` 0/1.
+    /// They are rendered with `bit_true`/`bit_false`, zero is false and any other value is true.
+    pub treat_as_bool: HashSet<String>,
+    /// Replacement text for NULL cells of numeric types, e.g. `I64` => `-1`, `F64` => `NaN`.
+    /// Meant for formats that can't express NULL in a numeric column. This is lossy: a sentinel
+    /// can't be told apart from a real value once exported. Non-numeric types are not affected.
+    pub numeric_null_sentinels: HashMap<OdbcColumnType, String>,
+    /// Renderer used by `Print`, the exporters ignore it.
+    pub renderer: TableRenderer,
+    /// Width of the printed table, `None` (default) detects it, see
+    /// [`odbc_common::print_table::table_width`]. Only used by `TableRenderer::Nu`.
+    pub terminal_width: Option<usize>,
+    /// Minimum width of every printed column, e.g. to keep the header of a 1-char flag readable.
+    /// Default is 0, the column is as wide as its widest cell.
+    pub min_col_width: usize,
+    /// Spaces added before and after every printed cell, on top of the renderer's own spacing.
+    /// Default is 0.
+    pub padding_left: usize,
+    pub padding_right: usize,
+    /// Per-column formatters keyed by column name, applied to non-NULL cells before the default
+    /// rendering, e.g. a unix timestamp integer shown as a date.
+    pub formatters: HashMap<String, CellFormatter>,
+    /// Case transform for the column names written by the exporters.
+    pub header_transform: HeaderCase,
+    /// Render `rowversion` columns (see `OdbcColumn::is_rowversion`) as their `u64` counter
+    /// instead of hex, default is false.
+    pub rowversion_as_number: bool,
+    /// Columns (matched by name) whose values are masked when rendered, e.g. PII in sample data.
+    /// NULLs stay NULL, the in-memory data is unchanged.
+    pub redact: HashMap<String, RedactStyle>,
+    /// Prepend a `#` column with the 1-based row number to the table, CSV and HTML output,
+    /// default is false. The data is unchanged and JSON/binary exports never include it.
+    pub with_row_numbers: bool,
+    /// Write the header row of the table, CSV and HTML output, default is true.
+    /// False writes data rows only, e.g. to append to an existing CSV file.
+    pub write_header: bool,
+    /// Display of `Time` and `Interval` cells, e.g. elapsed-time columns as `1h 30m`.
+    pub time_rendering: TimeRendering,
+    /// Render `Timestamp` and `Time` cells with exactly as many fractional digits as the
+    /// column's declared precision, e.g. `TIMESTAMP(3)` as `2022-01-02 03:04:05.120`, padding or
+    /// truncating the nanoseconds. Default is false, the stored text is rendered.
+    pub fixed_fraction_digits: bool,
+    /// Render `Timestamp` cells, taken as UTC, in the system's local timezone with its offset,
+    /// e.g. `2022-08-24 23:50:36.5 +08:00`. Default is false.
+    ///
+    /// `time` can't read the local offset while the process has several threads on some Unix
+    /// platforms (it isn't thread safe there), the cells are then rendered in UTC and a warning
+    /// is logged once.
+    pub local_timezone: bool,
+}
+
+/// Header of the row number column, see [`PrintOptions::with_row_numbers`].
+pub const ROW_NUMBER_HEADER: &str = "#";
+
+impl fmt::Debug for PrintOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrintOptions")
+            .field("null_text", &self.null_text)
+            .field("bit_true", &self.bit_true)
+            .field("bit_false", &self.bit_false)
+            .field("treat_as_bool", &self.treat_as_bool)
+            .field("numeric_null_sentinels", &self.numeric_null_sentinels)
+            .field("renderer", &self.renderer)
+            .field("terminal_width", &self.terminal_width)
+            .field("min_col_width", &self.min_col_width)
+            .field("padding_left", &self.padding_left)
+            .field("padding_right", &self.padding_right)
+            .field("formatters", &self.formatters.keys().collect::<Vec<_>>())
+            .field("header_transform", &self.header_transform)
+            .field("rowversion_as_number", &self.rowversion_as_number)
+            .field("redact", &self.redact)
+            .field("with_row_numbers", &self.with_row_numbers)
+            .field("write_header", &self.write_header)
+            .field("time_rendering", &self.time_rendering)
+            .field("fixed_fraction_digits", &self.fixed_fraction_digits)
+            .field("local_timezone", &self.local_timezone)
+            .finish()
+    }
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions {
+            null_text: String::new(),
+            bit_true: "true".to_string(),
+            bit_false: "false".to_string(),
+            treat_as_bool: HashSet::new(),
+            numeric_null_sentinels: HashMap::new(),
+            renderer: TableRenderer::default(),
+            terminal_width: None,
+            min_col_width: 0,
+            padding_left: 0,
+            padding_right: 0,
+            formatters: HashMap::new(),
+            header_transform: HeaderCase::default(),
+            rowversion_as_number: false,
+            redact: HashMap::new(),
+            with_row_numbers: false,
+            write_header: true,
+            time_rendering: TimeRendering::default(),
+            fixed_fraction_digits: false,
+            local_timezone: false,
+        }
+    }
+}
+
+impl PrintOptions {
+    /// Register a formatter for the column `name`, replacing any previous one.
+    pub fn add_formatter<F>(&mut self, name: impl Into<String>, formatter: F)
+    where
+        F: Fn(&OdbcColumnItem) -> String + Send + Sync + 'static,
+    {
+        self.formatters.insert(name.into(), Arc::new(formatter));
+    }
+
+    /// Display width per column of the header and the rendered cells, whichever is widest.
+    /// Wide characters (e.g. CJK) count as 2 and combining marks as 0.
+    pub fn column_widths(
+        &self,
+        columns: &[OdbcColumn],
+        rows: &[Vec<OdbcColumnItem>],
+    ) -> Vec<usize> {
+        let mut widths = columns
+            .iter()
+            .map(|column| display_width(&self.header(column)))
+            .collect::<Vec<_>>();
+        for row in rows {
+            for ((width, item), column) in widths.iter_mut().zip(row.iter()).zip(columns.iter()) {
+                *width = (*width).max(display_width(&self.render(item, column)));
+            }
+        }
+        widths
+    }
+
+    /// The exported name of `column`, see `header_transform`.
+    pub fn header(&self, column: &OdbcColumn) -> String {
+        self.header_transform.apply(&column.name)
+    }
+
+    fn render_null(&self, odbc_type: OdbcColumnType) -> String {
+        if odbc_type.is_numeric() {
+            if let Some(sentinel) = self.numeric_null_sentinels.get(&odbc_type) {
+                return sentinel.clone();
+            }
+        }
+        self.null_text.clone()
+    }
+
+    /// `None` when the value can't be parsed, it's then rendered as stored.
+    fn render_human_units(&self, item: &OdbcColumnItem) -> Option<String> {
+        let value = item.value.as_ref()?;
+        let interval = match item.odbc_type {
+            OdbcColumnType::Time => {
+                let time = parse_to_time(value.clone()).ok()?;
+                Interval {
+                    nanos: time.num_seconds_from_midnight() as i64 * 1_000_000_000
+                        + time.nanosecond() as i64,
+                    ..Default::default()
+                }
+            }
+            _ => Interval::parse(std::str::from_utf8(value).ok()?).ok()?,
+        };
+        Some(interval.human_units())
+    }
+
+    /// `None` when the column declares no precision or the value can't be parsed.
+    fn render_fixed_fraction(&self, item: &OdbcColumnItem, column: &OdbcColumn) -> Option<String> {
+        let precision = fraction_digits(&column.data_type)?.clamp(0, 9) as usize;
+        let value = item.value.as_ref()?;
+        let (text, nanos) = match item.odbc_type {
+            OdbcColumnType::Timestamp => {
+                let date_time = parse_to_data_time(value.clone()).ok()?;
+                (
+                    date_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    date_time.nanosecond(),
+                )
+            }
+            _ => {
+                let time = parse_to_time(value.clone()).ok()?;
+                (time.format("%H:%M:%S").to_string(), time.nanosecond())
+            }
+        };
+        if precision == 0 {
+            return Some(text);
+        }
+        let fraction = format!("{:09}", nanos % 1_000_000_000);
+        Some(format!("{}.{}", text, &fraction[..precision]))
+    }
+
+    /// `None` when the value can't be parsed.
+    fn render_local_timezone(&self, item: &OdbcColumnItem, column: &OdbcColumn) -> Option<String> {
+        let utc = parse_to_data_time(item.value.clone()?).ok()?;
+        let instant = time::OffsetDateTime::from_unix_timestamp(
+            chrono::TimeZone::from_utc_datetime(&chrono::Utc, &utc).timestamp(),
+        )
+        .ok()?;
+        let offset = time::UtcOffset::local_offset_at(instant).unwrap_or_else(|e| {
+            static WARN: Once = Once::new();
+            WARN.call_once(|| warn!("local timezone offset unavailable, rendering UTC:{}", e));
+            time::UtcOffset::UTC
+        });
+        let local = utc + chrono::Duration::seconds(offset.whole_seconds() as i64);
+        let local = OdbcColumnItem {
+            odbc_type: OdbcColumnType::Timestamp,
+            value: Some(
+                local
+                    .format("%Y-%m-%d %H:%M:%S%.f")
+                    .to_string()
+                    .as_str()
+                    .into(),
+            ),
+        };
+        let text = if self.fixed_fraction_digits {
+            self.render_fixed_fraction(&local, column)?
+        } else {
+            local.value_to_string()?
+        };
+        let (hours, minutes, _) = offset.as_hms();
+        Some(format!(
+            "{} {}{:02}:{:02}",
+            text,
+            if offset.is_negative() { '-' } else { '+' },
+            hours.abs(),
+            minutes.abs()
+        ))
+    }
+
+    fn render_bool(&self, value: bool) -> String {
+        if value {
+            self.bit_true.clone()
+        } else {
+            self.bit_false.clone()
+        }
+    }
+}
+
+/// [`PrintOptions::column_widths`] with the default options, e.g. for a fixed-layout renderer.
+pub fn column_widths(columns: &[OdbcColumn], rows: &[Vec<OdbcColumnItem>]) -> Vec<usize> {
+    PrintOptions::default().column_widths(columns, rows)
+}
+
+/// Turns a cell into the text written by an exporter, before any escaping or quoting the format
+/// itself needs. Each format options type implements it on top of [`PrintOptions`], so the
+/// table printer and the exporters agree on how a value looks.
+pub trait CellRenderer {
+    fn render(&self, item: &OdbcColumnItem, column: &OdbcColumn) -> String;
+}
+
+/// The display text used by the table printer, the other formats start from it.
+impl CellRenderer for PrintOptions {
+    fn render(&self, item: &OdbcColumnItem, column: &OdbcColumn) -> String {
+        let value = match &item.value {
+            Some(value) => value,
+            None => return self.render_null(item.odbc_type),
+        };
+        if let Some(style) = self.redact.get(&column.name) {
+            return style.apply(&item.value_to_string().unwrap_or_default());
+        }
+        if let Some(formatter) = self.formatters.get(&column.name) {
+            return formatter(item);
+        }
+
+        match item.odbc_type {
+            OdbcColumnType::Bit => self.render_bool(value.as_ref() == b"true"),
+            OdbcColumnType::Binary if self.rowversion_as_number && column.is_rowversion() => item
+                .as_rowversion()
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| item.value_to_string().unwrap_or_default()),
+            OdbcColumnType::Time | OdbcColumnType::Interval
+                if self.time_rendering == TimeRendering::HumanUnits =>
+            {
+                self.render_human_units(item)
+                    .unwrap_or_else(|| item.value_to_string().unwrap_or_default())
+            }
+            OdbcColumnType::Timestamp if self.local_timezone => self
+                .render_local_timezone(item, column)
+                .unwrap_or_else(|| item.value_to_string().unwrap_or_default()),
+            OdbcColumnType::Timestamp | OdbcColumnType::Time if self.fixed_fraction_digits => self
+                .render_fixed_fraction(item, column)
+                .unwrap_or_else(|| item.value_to_string().unwrap_or_default()),
+            OdbcColumnType::U8 if self.treat_as_bool.contains(&column.name) => {
+                self.render_bool(value.iter().any(|b| *b != 0))
+            }
+            OdbcColumnType::I8
+            | OdbcColumnType::I16
+            | OdbcColumnType::I32
+            | OdbcColumnType::I64
+                if self.treat_as_bool.contains(&column.name) =>
+            {
+                let value = String::from_utf8_lossy(value);
+                match value.parse::<i64>() {
+                    Ok(v) => self.render_bool(v != 0),
+                    Err(_) => value.to_string(),
+                }
+            }
+            _ => item.value_to_string().unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures::{self, item};
+    use crate::odbc_api::DataType;
+
+    #[test]
+    fn test_render_treat_as_bool() {
+        let column = OdbcColumn::new("enabled".to_string(), DataType::TinyInt, true);
+        let item = |v| fixtures::item(OdbcColumnType::I16, Some(v));
+
+        let mut options = PrintOptions::default();
+        assert_eq!(options.render(&item("1"), &column), "1");
+
+        options.treat_as_bool.insert("enabled".to_string());
+        assert_eq!(options.render(&item("1"), &column), "true");
+        assert_eq!(options.render(&item("0"), &column), "false");
+
+        let null = fixtures::item(OdbcColumnType::I16, None);
+        assert_eq!(options.render(&null, &column), "");
+    }
+
+    #[test]
+    fn test_render_numeric_null_sentinel() {
+        let mut options = PrintOptions::default();
+        options
+            .numeric_null_sentinels
+            .insert(OdbcColumnType::I64, "-1".to_string());
+        options
+            .numeric_null_sentinels
+            .insert(OdbcColumnType::Text, "n/a".to_string());
+
+        let column = OdbcColumn::new("c".to_string(), DataType::BigInt, true);
+        let null = |odbc_type| fixtures::item(odbc_type, None);
+        assert_eq!(options.render(&null(OdbcColumnType::I64), &column), "-1");
+        assert_eq!(options.render(&null(OdbcColumnType::F64), &column), "");
+        assert_eq!(options.render(&null(OdbcColumnType::Text), &column), "");
+    }
+
+    #[test]
+    fn test_render_formatter() {
+        let mut options = PrintOptions {
+            null_text: "-".to_string(),
+            ..Default::default()
+        };
+        options.add_formatter("status", |item| match item.value.as_deref() {
+            Some(b"1") => "active".to_string(),
+            _ => "inactive".to_string(),
+        });
+
+        let status = OdbcColumn::new("status".to_string(), DataType::Integer, true);
+        let other = OdbcColumn::new("other".to_string(), DataType::Integer, true);
+        let item = |v| fixtures::item(OdbcColumnType::I32, v);
+        assert_eq!(options.render(&item(Some("1")), &status), "active");
+        assert_eq!(options.render(&item(Some("0")), &status), "inactive");
+        assert_eq!(options.render(&item(None), &status), "-");
+        assert_eq!(options.render(&item(Some("1")), &other), "1");
+    }
+
+    #[test]
+    fn test_header_case() {
+        let names = ["user_id", "UserID", "HTTPServer name", "created At2"];
+        let apply = |case: HeaderCase| names.iter().map(|n| case.apply(n)).collect::<Vec<_>>();
+        assert_eq!(apply(HeaderCase::AsIs), names);
+        assert_eq!(
+            apply(HeaderCase::Upper),
+            vec!["USER_ID", "USERID", "HTTPSERVER NAME", "CREATED AT2"]
+        );
+        assert_eq!(
+            apply(HeaderCase::Snake),
+            vec!["user_id", "user_id", "http_server_name", "created_at2"]
+        );
+        assert_eq!(
+            apply(HeaderCase::Camel),
+            vec!["userId", "userId", "httpServerName", "createdAt2"]
+        );
+    }
+
+    #[test]
+    fn test_render_rowversion() {
+        let column = OdbcColumn::new("rv".to_string(), DataType::Binary { length: 8 }, false);
+        assert!(column.is_rowversion());
+        let item = fixtures::bytes_item(
+            OdbcColumnType::Binary,
+            Some(&[0u8, 0, 0, 0, 0, 0, 0x07, 0xd1]),
+        );
+        assert_eq!(item.as_rowversion(), Some(2001));
+
+        let mut options = PrintOptions::default();
+        assert_eq!(options.render(&item, &column), "0x00000000000007D1");
+        options.rowversion_as_number = true;
+        assert_eq!(options.render(&item, &column), "2001");
+    }
+
+    #[test]
+    fn test_render_redact() {
+        let mut options = PrintOptions::default();
+        options
+            .redact
+            .insert("email".to_string(), RedactStyle::Stars);
+        options.redact.insert(
+            "phone".to_string(),
+            RedactStyle::Mask("<hidden>".to_string()),
+        );
+        let salted = |salt: &str| RedactStyle::Hash {
+            salt: salt.to_string(),
+        };
+        options.redact.insert("name".to_string(), salted("pepper"));
+
+        let column =
+            |name: &str| OdbcColumn::new(name.to_string(), DataType::Varchar { length: 32 }, true);
+        let item = |v| fixtures::item(OdbcColumnType::Text, v);
+        assert_eq!(
+            options.render(&item(Some("a@b.c")), &column("email")),
+            "*****"
+        );
+        assert_eq!(
+            options.render(&item(Some("123")), &column("phone")),
+            "<hidden>"
+        );
+        assert_eq!(options.render(&item(None), &column("phone")), "");
+        let hash = options.render(&item(Some("bob")), &column("name"));
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, options.render(&item(Some("bob")), &column("name")));
+        assert_ne!(hash, options.render(&item(Some("alice")), &column("name")));
+        assert_eq!(hash, salted("pepper").apply("bob"));
+        assert_ne!(hash, salted("salt").apply("bob"));
+        // Pinned so the same value keeps the same hash across toolchains and releases.
+        assert_eq!(salted("").apply("abc"), "ba7816bf8f01cfea");
+    }
+
+    #[test]
+    fn test_renderers_agree_on_scalars() {
+        use crate::export::csv::CsvOptions;
+        use crate::export::html::HtmlOptions;
+        use crate::export::json::JsonOptions;
+
+        let cells = [
+            (DataType::Integer, OdbcColumnType::I32, Some(&b"42"[..])),
+            (DataType::BigInt, OdbcColumnType::I64, Some(b"-7")),
+            (DataType::Double, OdbcColumnType::F64, Some(b"1.5")),
+            (DataType::TinyInt, OdbcColumnType::U8, Some(&[3u8][..])),
+            (
+                DataType::Varchar { length: 10 },
+                OdbcColumnType::Text,
+                Some(b"a,\"b<"),
+            ),
+            (DataType::Bit, OdbcColumnType::Bit, Some(b"true")),
+            (DataType::Date, OdbcColumnType::Date, Some(b"2022-08-24")),
+            (DataType::Integer, OdbcColumnType::I32, None),
+        ];
+        let renderers: Vec<Box<dyn CellRenderer>> = vec![
+            Box::new(CsvOptions::default()),
+            Box::new(HtmlOptions::default()),
+            Box::new(JsonOptions::default()),
+        ];
+        let table = PrintOptions::default();
+        for (data_type, odbc_type, value) in cells {
+            let column = OdbcColumn::new("c".to_string(), data_type, true);
+            let item = fixtures::bytes_item(odbc_type, value);
+            let expected = table.render(&item, &column);
+            for renderer in renderers.iter() {
+                assert_eq!(renderer.render(&item, &column), expected, "{:?}", item);
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_time_human_units() {
+        let column = OdbcColumn::new("elapsed".to_string(), DataType::Time { precision: 0 }, true);
+        let item = |odbc_type, v| fixtures::item(odbc_type, Some(v));
+        let options = PrintOptions {
+            time_rendering: TimeRendering::HumanUnits,
+            ..Default::default()
+        };
+        let time = item(OdbcColumnType::Time, "01:30:00");
+        assert_eq!(PrintOptions::default().render(&time, &column), "01:30:00");
+        assert_eq!(options.render(&time, &column), "1h 30m");
+        assert_eq!(
+            options.render(&item(OdbcColumnType::Time, "00:00:05.25"), &column),
+            "5.25s"
+        );
+        assert_eq!(
+            options.render(&item(OdbcColumnType::Interval, "P1DT2H"), &column),
+            "1d 2h"
+        );
+        assert_eq!(
+            options.render(&item(OdbcColumnType::Interval, "bogus"), &column),
+            "bogus"
+        );
+    }
+
+    #[test]
+    fn test_render_fixed_fraction_digits() {
+        let column = |data_type| OdbcColumn::new("t".to_string(), data_type, true);
+        let item = |odbc_type, v| fixtures::item(odbc_type, Some(v));
+        let options = PrintOptions {
+            fixed_fraction_digits: true,
+            ..Default::default()
+        };
+        let timestamp = item(OdbcColumnType::Timestamp, "2022-01-02 3:04:05.12");
+        assert_eq!(
+            options.render(&timestamp, &column(DataType::Timestamp { precision: 3 })),
+            "2022-01-02 03:04:05.120"
+        );
+        assert_eq!(
+            options.render(&timestamp, &column(DataType::Timestamp { precision: 0 })),
+            "2022-01-02 03:04:05"
+        );
+        assert_eq!(
+            options.render(
+                &item(OdbcColumnType::Time, "10:00:00.123456789"),
+                &column(DataType::Time { precision: 6 })
+            ),
+            "10:00:00.123456"
+        );
+        assert_eq!(
+            PrintOptions::default()
+                .render(&timestamp, &column(DataType::Timestamp { precision: 3 })),
+            "2022-01-02 3:04:05.12"
+        );
+    }
+
+    #[test]
+    fn test_column_widths() {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, false),
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 20 }, true),
+            OdbcColumn::new("ok".to_string(), DataType::Bit, true),
+        ];
+        let rows = vec![
+            vec![
+                item(OdbcColumnType::I32, Some("12345")),
+                item(OdbcColumnType::Text, Some("\u{4e16}\u{754c}")),
+                item(OdbcColumnType::Bit, Some("false")),
+            ],
+            vec![
+                item(OdbcColumnType::I32, Some("1")),
+                item(OdbcColumnType::Text, Some("e\u{301}")),
+                item(OdbcColumnType::Bit, None),
+            ],
+        ];
+        assert_eq!(column_widths(&columns, &rows), vec![5, 4, 5]);
+        assert_eq!(column_widths(&columns, &[]), vec![2, 4, 2]);
+    }
+
+    #[test]
+    fn test_render_local_timezone() {
+        let column = OdbcColumn::new(
+            "created".to_string(),
+            DataType::Timestamp { precision: 3 },
+            true,
+        );
+        let item = fixtures::item(OdbcColumnType::Timestamp, Some("2022-08-24 15:50:36.5"));
+        let options = PrintOptions {
+            local_timezone: true,
+            fixed_fraction_digits: true,
+            ..Default::default()
+        };
+        let text = options.render(&item, &column);
+        // the offset depends on the machine, or is UTC when it can't be read
+        let (local, offset) = text.rsplit_once(' ').unwrap();
+        assert!(
+            offset.len() == 6 && offset.starts_with(['+', '-']),
+            "{}",
+            text
+        );
+        assert!(local.ends_with(":36.500"), "{}", text);
+        if offset == "+00:00" {
+            assert_eq!(local, "2022-08-24 15:50:36.500");
+        }
+    }
+}
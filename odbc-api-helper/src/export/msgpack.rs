@@ -0,0 +1,141 @@
+//! MessagePack export, a self-describing map more compact and faster to parse than JSON:
+//! ```text
+//! {"columns": [name, ...], "rows": [[value, ...], ...]}
+//! ```
+//! Values are typed by the cell's `OdbcColumnType`: NULL is nil, `Bit` a bool, the integer types
+//! ints, `F32`/`F64` floats, `Binary` bin, `Json` the nested document and every other type the
+//! driver's text as str.
+use crate::extension::odbc::{OdbcColumn, OdbcColumnItem, OdbcColumnType};
+use rmp::encode;
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// Write `columns` and `data` to `w` as the MessagePack map described in the module docs.
+pub fn write_msgpack<W: Write>(
+    w: &mut W,
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+) -> io::Result<()> {
+    encode::write_map_len(w, 2)?;
+    encode::write_str(w, "columns")?;
+    encode::write_array_len(w, columns.len() as u32)?;
+    for column in columns {
+        encode::write_str(w, &column.name)?;
+    }
+    encode::write_str(w, "rows")?;
+    encode::write_array_len(w, data.len() as u32)?;
+    for row in data {
+        encode::write_array_len(w, row.len() as u32)?;
+        for (item, column) in row.iter().zip(columns) {
+            write_cell(w, item, column)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_cell<W: Write>(w: &mut W, item: &OdbcColumnItem, column: &OdbcColumn) -> io::Result<()> {
+    let value = match &item.value {
+        Some(value) => value,
+        None => return encode::write_nil(w),
+    };
+    let text = || String::from_utf8_lossy(value);
+    let invalid = |e: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "column `{}` value is not a valid {:?}:{}",
+                column.name, item.odbc_type, e
+            ),
+        )
+    };
+
+    match item.odbc_type {
+        OdbcColumnType::Bit => encode::write_bool(w, value.as_ref() == b"true")?,
+        OdbcColumnType::U8 => {
+            encode::write_uint(w, value.first().copied().unwrap_or_default().into())?;
+        }
+        OdbcColumnType::I8 | OdbcColumnType::I16 | OdbcColumnType::I32 | OdbcColumnType::I64 => {
+            let v = text().parse::<i64>().map_err(|e| invalid(e.to_string()))?;
+            encode::write_sint(w, v)?;
+        }
+        OdbcColumnType::U64 => {
+            let v = text().parse::<u64>().map_err(|e| invalid(e.to_string()))?;
+            encode::write_uint(w, v)?;
+        }
+        OdbcColumnType::F32 | OdbcColumnType::F64 => {
+            let v = text().parse::<f64>().map_err(|e| invalid(e.to_string()))?;
+            encode::write_f64(w, v)?;
+        }
+        OdbcColumnType::Binary => encode::write_bin(w, value)?,
+        OdbcColumnType::Json => {
+            let v = serde_json::from_slice(value).map_err(|e| invalid(e.to_string()))?;
+            write_json_value(w, &v)?;
+        }
+        _ => encode::write_str(w, &text())?,
+    }
+    Ok(())
+}
+
+fn write_json_value<W: Write>(w: &mut W, value: &Value) -> io::Result<()> {
+    match value {
+        Value::Null => encode::write_nil(w)?,
+        Value::Bool(v) => encode::write_bool(w, *v)?,
+        Value::Number(v) => {
+            if let Some(v) = v.as_u64() {
+                encode::write_uint(w, v)?;
+            } else if let Some(v) = v.as_i64() {
+                encode::write_sint(w, v)?;
+            } else {
+                encode::write_f64(w, v.as_f64().unwrap_or_default())?;
+            }
+        }
+        Value::String(v) => encode::write_str(w, v)?,
+        Value::Array(values) => {
+            encode::write_array_len(w, values.len() as u32)?;
+            for v in values {
+                write_json_value(w, v)?;
+            }
+        }
+        Value::Object(map) => {
+            encode::write_map_len(w, map.len() as u32)?;
+            for (k, v) in map {
+                encode::write_str(w, k)?;
+                write_json_value(w, v)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures;
+    use rmp::decode;
+
+    #[test]
+    fn test_write_msgpack() {
+        let (columns, data) = fixtures::all_types();
+        let mut buf = Vec::new();
+        write_msgpack(&mut buf, &columns, &data).unwrap();
+
+        let mut r = buf.as_slice();
+        let text = |r: &mut &[u8]| {
+            let mut out = [0u8; 32];
+            decode::read_str(r, &mut out).unwrap().to_string()
+        };
+        assert_eq!(decode::read_map_len(&mut r).unwrap(), 2);
+        assert_eq!(text(&mut r), "columns");
+        assert_eq!(decode::read_array_len(&mut r).unwrap(), 19);
+        let names = (0..19).map(|_| text(&mut r)).collect::<Vec<_>>();
+        assert_eq!(names[0], "text");
+        assert_eq!(text(&mut r), "rows");
+        assert_eq!(decode::read_array_len(&mut r).unwrap(), 3);
+
+        assert_eq!(decode::read_array_len(&mut r).unwrap(), 19);
+        assert_eq!(text(&mut r), "plain");
+        assert_eq!(text(&mut r), "wide");
+        assert_eq!(decode::read_bin_len(&mut r).unwrap(), 2);
+        assert_eq!(&r[..2], &[0x01, 0x02]);
+    }
+}
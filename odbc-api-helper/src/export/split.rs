@@ -0,0 +1,181 @@
+//! Export every column of a result set to its own file, e.g. to feed columnar tools or to look
+//! at a single problematic column.
+use crate::export::json::{cell_value, JsonOptions};
+use crate::export::{CellRenderer, PrintOptions};
+use crate::extension::odbc::{OdbcColumn, OdbcColumnItem};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Format of the per-column files written by [`export_columns_split`].
+#[derive(Debug, Clone)]
+pub enum SplitFormat {
+    /// `<name>.txt`, one rendered value per line. Backslashes and line breaks in values are
+    /// escaped as `\\`, `\n` and `\r`, NULL is `null_text`.
+    Lines(PrintOptions),
+    /// `<name>.json`, a JSON array of the values as written by `write_json`.
+    Json(JsonOptions),
+}
+
+impl SplitFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            SplitFormat::Lines(_) => "txt",
+            SplitFormat::Json(_) => "json",
+        }
+    }
+
+    fn print(&self) -> &PrintOptions {
+        match self {
+            SplitFormat::Lines(print) => print,
+            SplitFormat::Json(options) => &options.print,
+        }
+    }
+}
+
+/// Write each column to `dir/<name>.<ext>`, returns the paths in column order.
+///
+/// Names follow `print.header_transform`, characters other than ASCII letters, digits, `_`,
+/// `-` and `.` are replaced by `_`. An empty name becomes `column_<n>`. A name already used,
+/// ignoring case for case-insensitive file systems, gets the column number appended, counting up
+/// until it is unused, so every column gets its own file.
+/// `dir` must exist, existing files are overwritten.
+pub fn export_columns_split(
+    dir: &Path,
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+    format: &SplitFormat,
+) -> io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = Vec::with_capacity(columns.len());
+    let mut used = HashSet::with_capacity(columns.len());
+    for (index, column) in columns.iter().enumerate() {
+        let mut base = file_name(&format.print().header(column));
+        if base.is_empty() {
+            base = format!("column_{}", index + 1);
+        }
+        let mut name = base.clone();
+        let mut suffix = index + 1;
+        while !used.insert(name.to_lowercase()) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        let path = dir.join(format!("{}.{}", name, format.extension()));
+
+        let mut w = BufWriter::new(File::create(&path)?);
+        let cells = data.iter().filter_map(|row| row.get(index));
+        match format {
+            SplitFormat::Lines(print) => {
+                for item in cells {
+                    writeln!(w, "{}", escape_line(&print.render(item, column)))?;
+                }
+            }
+            SplitFormat::Json(options) => {
+                let values = cells
+                    .map(|item| cell_value(item, column, options))
+                    .collect::<io::Result<Vec<_>>>()?;
+                serde_json::to_writer(&mut w, &values)?;
+            }
+        }
+        w.flush()?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
+
+fn escape_line(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures::item;
+    use crate::extension::odbc::OdbcColumnType;
+    use crate::odbc_api::DataType;
+    use std::fs;
+
+    #[test]
+    fn test_export_columns_split() {
+        let dir = std::env::temp_dir().join(format!("odbc-split-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, false),
+            OdbcColumn::new("a/b".to_string(), DataType::Varchar { length: 20 }, true),
+            OdbcColumn::new("id".to_string(), DataType::Integer, true),
+        ];
+        let data = vec![
+            vec![
+                item(OdbcColumnType::I32, Some("1")),
+                item(OdbcColumnType::Text, Some("x\ny")),
+                item(OdbcColumnType::I32, None),
+            ],
+            vec![
+                item(OdbcColumnType::I32, Some("2")),
+                item(OdbcColumnType::Text, None),
+                item(OdbcColumnType::I32, Some("3")),
+            ],
+        ];
+
+        let format = SplitFormat::Lines(PrintOptions::default());
+        let paths = export_columns_split(&dir, &columns, &data, &format).unwrap();
+        let names = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["id.txt", "a_b.txt", "id_3.txt"]);
+        assert_eq!(fs::read_to_string(&paths[1]).unwrap(), "x\\ny\n\n");
+
+        let format = SplitFormat::Json(JsonOptions::default());
+        let paths = export_columns_split(&dir, &columns, &data, &format).unwrap();
+        assert_eq!(fs::read_to_string(&paths[0]).unwrap(), "[1,2]");
+        assert_eq!(fs::read_to_string(&paths[2]).unwrap(), "[null,3]");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_columns_split_unique_names() {
+        let dir = std::env::temp_dir().join(format!("odbc-split-unique-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let format = SplitFormat::Lines(PrintOptions::default());
+        for (headers, expected) in [
+            (vec!["a_3", "a", "a"], vec!["a_3.txt", "a.txt", "a_4.txt"]),
+            (vec!["column_2", ""], vec!["column_2.txt", "column_2_2.txt"]),
+            (vec!["Id", "id"], vec!["Id.txt", "id_2.txt"]),
+        ] {
+            let columns = headers
+                .iter()
+                .map(|name| OdbcColumn::new(name.to_string(), DataType::Integer, true))
+                .collect::<Vec<_>>();
+            let paths = export_columns_split(&dir, &columns, &[], &format).unwrap();
+            let names = paths
+                .iter()
+                .map(|p| p.file_name().unwrap().to_str().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(names, expected);
+            let distinct = paths.iter().collect::<HashSet<_>>();
+            assert_eq!(distinct.len(), paths.len());
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,226 @@
+//! Export straight from an ODBC cursor, writing every batch as soon as it is fetched.
+//! At most one batch of `Options::max_batch_size` rows is held in memory, so tables of any
+//! size can be exported.
+use crate::executor::database::{for_each_batch, Options};
+use crate::export::csv::{write_csv_header, write_csv_rows_at, CsvOptions};
+use crate::export::json::{write_json_lines, JsonOptions};
+use crate::export::{CellRenderer, PrintOptions, ROW_NUMBER_HEADER};
+use crate::extension::odbc::{OdbcColumn, OdbcColumnItem};
+use crate::odbc_api::Cursor;
+use odbc_common::print_table::{cell_widths, format_table_row, table_separator};
+use std::io::Write;
+
+/// Called after every written batch with the number of rows written so far,
+/// e.g. to show `exported N rows...`.
+pub type Progress<'a> = Box<dyn FnMut(usize) + 'a>;
+
+/// Stream the cursor as CSV, returns the number of rows written.
+/// `columns` describe the cursor, see `OdbcDbConnection::get_cursor_columns`.
+pub fn stream_csv<C: Cursor, W: Write>(
+    cursor: C,
+    columns: &[OdbcColumn],
+    options: &Options,
+    csv_options: &CsvOptions,
+    w: &mut W,
+    mut progress: Option<Progress>,
+) -> anyhow::Result<usize> {
+    write_csv_header(w, columns, csv_options)?;
+    let mut written = 0;
+    let rows = for_each_batch(cursor, columns, options, |batch| {
+        write_csv_rows_at(w, columns, &batch, written + 1, csv_options)?;
+        written += batch.len();
+        if let Some(progress) = progress.as_mut() {
+            progress(written);
+        }
+        Ok(())
+    })?;
+    w.flush()?;
+    Ok(rows)
+}
+
+/// Stream the cursor as JSON lines, returns the number of rows written.
+pub fn stream_json_lines<C: Cursor, W: Write>(
+    cursor: C,
+    columns: &[OdbcColumn],
+    options: &Options,
+    json_options: &JsonOptions,
+    w: &mut W,
+    mut progress: Option<Progress>,
+) -> anyhow::Result<usize> {
+    let mut written = 0;
+    let rows = for_each_batch(cursor, columns, options, |batch| {
+        write_json_lines(w, columns, &batch, json_options)?;
+        written += batch.len();
+        if let Some(progress) = progress.as_mut() {
+            progress(written);
+        }
+        Ok(())
+    })?;
+    w.flush()?;
+    Ok(rows)
+}
+
+/// Stream the cursor as a plain text table like `TableRenderer::Simple`, header once and then
+/// the rows of every batch as soon as it is fetched, e.g. to pipe a large result into a pager.
+/// Returns the number of rows written.
+///
+/// Column widths are the display widths (wide characters count as 2) of the header and the
+/// first batch only and kept for the later batches, so a wider value fetched later overflows its
+/// column and shifts the rest of its line.
+/// NULL, `header_transform`, row numbers and `write_header` follow `print`, padding is ignored.
+pub fn stream_table<C: Cursor, W: Write>(
+    cursor: C,
+    columns: &[OdbcColumn],
+    options: &Options,
+    print: &PrintOptions,
+    w: &mut W,
+    mut progress: Option<Progress>,
+) -> anyhow::Result<usize> {
+    let mut writer = TableWriter::new(columns, print);
+    let rows = for_each_batch(cursor, columns, options, |batch| {
+        writer.write_batch(w, &batch)?;
+        w.flush()?;
+        if let Some(progress) = progress.as_mut() {
+            progress(writer.written);
+        }
+        Ok(())
+    })?;
+    writer.finish(w)?;
+    w.flush()?;
+    Ok(rows)
+}
+
+/// Incremental table output of [`stream_table`], widths are fixed by the first batch.
+struct TableWriter<'a> {
+    columns: &'a [OdbcColumn],
+    print: &'a PrintOptions,
+    widths: Option<Vec<usize>>,
+    written: usize,
+}
+
+impl<'a> TableWriter<'a> {
+    fn new(columns: &'a [OdbcColumn], print: &'a PrintOptions) -> Self {
+        TableWriter {
+            columns,
+            print,
+            widths: None,
+            written: 0,
+        }
+    }
+
+    fn headers(&self) -> Vec<String> {
+        if !self.print.write_header {
+            return vec![];
+        }
+        let row_number = self
+            .print
+            .with_row_numbers
+            .then(|| ROW_NUMBER_HEADER.to_string());
+        row_number
+            .into_iter()
+            .chain(self.columns.iter().map(|c| self.print.header(c)))
+            .collect()
+    }
+
+    fn render(&self, index: usize, row: &[OdbcColumnItem]) -> Vec<String> {
+        let row_number = self.print.with_row_numbers.then(|| index.to_string());
+        row_number
+            .into_iter()
+            .chain(
+                row.iter()
+                    .zip(self.columns.iter())
+                    .map(|(item, column)| self.print.render(item, column)),
+            )
+            .collect()
+    }
+
+    fn write_batch<W: Write>(
+        &mut self,
+        w: &mut W,
+        batch: &[Vec<OdbcColumnItem>],
+    ) -> std::io::Result<()> {
+        let rows = batch
+            .iter()
+            .enumerate()
+            .map(|(index, row)| self.render(self.written + index + 1, row))
+            .collect::<Vec<_>>();
+        if self.widths.is_none() {
+            self.write_header(w, &rows)?;
+        }
+        let widths = self.widths.as_deref().unwrap_or_default();
+        for row in rows.iter() {
+            writeln!(w, "{}", format_table_row(row, widths))?;
+        }
+        self.written += rows.len();
+        Ok(())
+    }
+
+    fn write_header<W: Write>(&mut self, w: &mut W, rows: &[Vec<String>]) -> std::io::Result<()> {
+        let headers = self.headers();
+        let widths = cell_widths(&headers, rows);
+        if !headers.is_empty() {
+            writeln!(w, "{}", format_table_row(&headers, &widths))?;
+            writeln!(w, "{}", table_separator(&widths))?;
+        }
+        self.widths = Some(widths);
+        Ok(())
+    }
+
+    /// The header of a result without rows.
+    fn finish<W: Write>(&mut self, w: &mut W) -> std::io::Result<()> {
+        if self.widths.is_none() {
+            self.write_header(w, &[])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures;
+    use crate::extension::odbc::OdbcColumnType;
+    use crate::odbc_api::DataType;
+
+    #[test]
+    fn test_table_writer() {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, false),
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 20 }, true),
+        ];
+        let row = |id: &str, name: &str| {
+            vec![
+                fixtures::item(OdbcColumnType::I32, Some(id)),
+                fixtures::item(OdbcColumnType::Text, Some(name)),
+            ]
+        };
+        let print = PrintOptions::default();
+        let mut writer = TableWriter::new(&columns, &print);
+        let mut buf = vec![];
+        writer.write_batch(&mut buf, &[row("1", "ab")]).unwrap();
+        writer.write_batch(&mut buf, &[row("100", "c")]).unwrap();
+        writer.finish(&mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "id | name\n---+-----\n1  | ab\n100 | c\n"
+        );
+
+        let mut writer = TableWriter::new(&columns, &print);
+        let mut buf = vec![];
+        writer.finish(&mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "id | name\n---+-----\n");
+
+        let mut writer = TableWriter::new(&columns, &print);
+        let mut buf = vec![];
+        writer
+            .write_batch(
+                &mut buf,
+                &[row("1", "\u{4e16}\u{754c}\u{4e16}"), row("2", "abc")],
+            )
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "id | name\n---+-------\n1  | \u{4e16}\u{754c}\u{4e16}\n2  | abc\n"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Dates and timestamps stored as integers, days or seconds since a vendor specific epoch.
+use crate::error::OdbcHelperError;
+use crate::extension::odbc::{OdbcColumnItem, OdbcColumnType};
+use bytes::BytesMut;
+use time::macros::date;
+use time::{Date, Duration, PrimitiveDateTime, Time};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Day 0 of an integer encoded date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Epoch {
+    /// 1970-01-01.
+    Unix,
+    /// Excel's 1900 date system, serial 1 is 1900-01-01.
+    ///
+    /// Excel (after Lotus 1-2-3) treats 1900 as a leap year: serial 60 is the nonexistent
+    /// 1900-02-29 and every later serial is one day ahead. With `leap_year_bug` serials match
+    /// Excel, 61 is 1900-03-01 and 60 is an error. Without it days are counted exactly from
+    /// 1899-12-31, use it for data not produced by Excel.
+    Excel1900 { leap_year_bug: bool },
+    /// Any day 0, e.g. `0001-01-01` for .NET `DateTime` days.
+    Custom(Date),
+}
+
+/// Unit of the integers decoded by [`decode_epoch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpochUnit {
+    /// Days, decoded to `Date`.
+    Days,
+    /// Seconds, decoded to `Timestamp`.
+    Seconds,
+}
+
+impl Epoch {
+    /// The date `days` after the epoch.
+    pub fn date(&self, days: i64) -> Result<Date, OdbcHelperError> {
+        let (day_zero, days) = match *self {
+            Epoch::Unix => (date!(1970 - 01 - 01), days),
+            Epoch::Excel1900 {
+                leap_year_bug: true,
+            } if days == 60 => {
+                return Err(OdbcHelperError::InvalidDate {
+                    year: 1900,
+                    month: 2,
+                    day: 29,
+                })
+            }
+            Epoch::Excel1900 {
+                leap_year_bug: true,
+            } if days > 60 => (date!(1899 - 12 - 30), days),
+            Epoch::Excel1900 { .. } => (date!(1899 - 12 - 31), days),
+            Epoch::Custom(day_zero) => (day_zero, days),
+        };
+        days.checked_mul(SECONDS_PER_DAY)
+            .and_then(|seconds| day_zero.checked_add(Duration::seconds(seconds)))
+            .ok_or(OdbcHelperError::InvalidTimestamp {
+                field: "days",
+                value: days,
+            })
+    }
+
+    /// The timestamp `seconds` after midnight of the epoch.
+    pub fn timestamp(&self, seconds: i64) -> Result<PrimitiveDateTime, OdbcHelperError> {
+        let date = self.date(seconds.div_euclid(SECONDS_PER_DAY))?;
+        let time = Time::MIDNIGHT + Duration::seconds(seconds.rem_euclid(SECONDS_PER_DAY));
+        Ok(PrimitiveDateTime::new(date, time))
+    }
+}
+
+/// Decode the integer cells of a column, `I8`..`I64` and `U8`, to `Date` or `Timestamp` cells.
+/// Other cells are left as is, an integer cell with invalid text is an error.
+pub fn decode_epoch(
+    items: &mut [OdbcColumnItem],
+    unit: EpochUnit,
+    epoch: Epoch,
+) -> Result<(), OdbcHelperError> {
+    for item in items.iter_mut() {
+        let v = match item.odbc_type {
+            OdbcColumnType::F32 | OdbcColumnType::F64 => continue,
+            t if t.is_numeric() => item.as_i64(),
+            _ => continue,
+        };
+        if v.is_none() && item.value.is_some() {
+            return Err(OdbcHelperError::TypeConversionError(format!(
+                "epoch {:?}, invalid {:?} `{}`",
+                unit,
+                item.odbc_type,
+                item.value_to_string().unwrap_or_default()
+            )));
+        }
+        item.value = match (v, unit) {
+            (None, _) => None,
+            (Some(v), EpochUnit::Days) => Some(epoch.date(v)?.to_string()),
+            (Some(v), EpochUnit::Seconds) => Some(epoch.timestamp(v)?.to_string()),
+        }
+        .map(|text| BytesMut::from(text.as_str()));
+        item.odbc_type = match unit {
+            EpochUnit::Days => OdbcColumnType::Date,
+            EpochUnit::Seconds => OdbcColumnType::Timestamp,
+        };
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures::item;
+
+    #[test]
+    fn test_epoch_date() {
+        assert_eq!(Epoch::Unix.date(19_000).unwrap(), date!(2022 - 01 - 08));
+        assert_eq!(Epoch::Unix.date(-1).unwrap(), date!(1969 - 12 - 31));
+        assert_eq!(
+            Epoch::Custom(date!(0001 - 01 - 01)).date(1).unwrap(),
+            date!(0001 - 01 - 02)
+        );
+
+        let excel = Epoch::Excel1900 {
+            leap_year_bug: true,
+        };
+        assert_eq!(excel.date(1).unwrap(), date!(1900 - 01 - 01));
+        assert_eq!(excel.date(59).unwrap(), date!(1900 - 02 - 28));
+        assert!(excel.date(60).is_err());
+        assert_eq!(excel.date(61).unwrap(), date!(1900 - 03 - 01));
+        assert_eq!(excel.date(44_927).unwrap(), date!(2023 - 01 - 01));
+        let exact = Epoch::Excel1900 {
+            leap_year_bug: false,
+        };
+        assert_eq!(exact.date(61).unwrap(), date!(1900 - 03 - 02));
+
+        assert!(Epoch::Unix.date(i64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_decode_epoch() {
+        let mut items = vec![
+            item(OdbcColumnType::I64, Some("1661356236")),
+            item(OdbcColumnType::I64, Some("-1")),
+            item(OdbcColumnType::I64, None),
+            item(OdbcColumnType::Text, Some("x")),
+        ];
+        decode_epoch(&mut items, EpochUnit::Seconds, Epoch::Unix).unwrap();
+        assert_eq!(
+            items,
+            vec![
+                item(OdbcColumnType::Timestamp, Some("2022-08-24 15:50:36.0")),
+                item(OdbcColumnType::Timestamp, Some("1969-12-31 23:59:59.0")),
+                item(OdbcColumnType::Timestamp, None),
+                item(OdbcColumnType::Text, Some("x")),
+            ]
+        );
+    }
+}
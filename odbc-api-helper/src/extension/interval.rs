@@ -0,0 +1,402 @@
+//! SQL `INTERVAL` values. An interval has independent month, day and time components
+//! (a month has no fixed number of days), so it can't be reduced to a single duration.
+use std::fmt;
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const NANOS_PER_MINUTE: i64 = 60 * NANOS_PER_SECOND;
+const NANOS_PER_HOUR: i64 = 60 * NANOS_PER_MINUTE;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    /// time component in nanoseconds
+    pub nanos: i64,
+}
+
+impl Interval {
+    /// Parse the common text forms of an interval:
+    /// - Postgres `postgres` style: `1 year 2 mons -3 days 04:05:06.5`
+    /// - Postgres `postgres_verbose` style: `@ 1 year 2 mons 4 hours 6.5 secs ago`
+    /// - SQL standard / Oracle: `+1-2 +3 04:05:06`, `+02 03:04:05.000000`, `-01-02`
+    /// - ISO-8601 duration: `P1Y2M3DT4H5M6.5S`, `P-1Y-2M`, `-PT5M`
+    pub fn parse(text: &str) -> Result<Interval, String> {
+        let text = text.trim();
+        let invalid = || format!("invalid interval:`{}`", text);
+        let iso = text
+            .strip_prefix('P')
+            .map(|t| (t, false))
+            .or_else(|| text.strip_prefix("-P").map(|t| (t, true)));
+        match iso {
+            Some((t, negative)) => {
+                let interval = parse_iso(t).ok_or_else(invalid)?;
+                Ok(if negative {
+                    interval.negate()
+                } else {
+                    interval
+                })
+            }
+            None => parse_words(text).ok_or_else(invalid),
+        }
+    }
+
+    /// The interval with explicit units for display, e.g. `1y 2mo 3d 1h 30m 5.5s`, `0s` when
+    /// empty. Components keep their own sign.
+    pub fn human_units(&self) -> String {
+        if *self == Interval::default() {
+            return "0s".to_string();
+        }
+        let mut parts = vec![];
+        let (years, months) = (self.months / 12, self.months % 12);
+        let hours = self.nanos / NANOS_PER_HOUR;
+        let minutes = self.nanos % NANOS_PER_HOUR / NANOS_PER_MINUTE;
+        for (value, unit) in [
+            (years as i64, "y"),
+            (months as i64, "mo"),
+            (self.days as i64, "d"),
+            (hours, "h"),
+            (minutes, "m"),
+        ] {
+            if value != 0 {
+                parts.push(format!("{}{}", value, unit));
+            }
+        }
+        let nanos = self.nanos % NANOS_PER_MINUTE;
+        if nanos != 0 {
+            let sign = if nanos < 0 { "-" } else { "" };
+            let nanos = nanos.unsigned_abs();
+            let fraction = format!("{:09}", nanos % NANOS_PER_SECOND as u64);
+            let fraction = fraction.trim_end_matches('0');
+            let seconds = nanos / NANOS_PER_SECOND as u64;
+            parts.push(if fraction.is_empty() {
+                format!("{}{}s", sign, seconds)
+            } else {
+                format!("{}{}.{}s", sign, seconds, fraction)
+            });
+        }
+        parts.join(" ")
+    }
+
+    fn negate(self) -> Interval {
+        Interval {
+            months: -self.months,
+            days: -self.days,
+            nanos: -self.nanos,
+        }
+    }
+}
+
+/// Canonical ISO-8601 form, e.g. `P1Y2M3DT4H5M6.5S`, `PT0S` when empty.
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == Interval::default() {
+            return write!(f, "PT0S");
+        }
+        write!(f, "P")?;
+        let (years, months) = (self.months / 12, self.months % 12);
+        for (value, unit) in [
+            (years as i64, 'Y'),
+            (months as i64, 'M'),
+            (self.days as i64, 'D'),
+        ] {
+            if value != 0 {
+                write!(f, "{}{}", value, unit)?;
+            }
+        }
+        if self.nanos == 0 {
+            return Ok(());
+        }
+        write!(f, "T")?;
+        let hours = self.nanos / NANOS_PER_HOUR;
+        let minutes = self.nanos % NANOS_PER_HOUR / NANOS_PER_MINUTE;
+        let nanos = self.nanos % NANOS_PER_MINUTE;
+        if hours != 0 {
+            write!(f, "{}H", hours)?;
+        }
+        if minutes != 0 {
+            write!(f, "{}M", minutes)?;
+        }
+        if nanos != 0 {
+            let sign = if nanos < 0 { "-" } else { "" };
+            let nanos = nanos.unsigned_abs();
+            write!(f, "{}{}", sign, nanos / NANOS_PER_SECOND as u64)?;
+            let fraction = nanos % NANOS_PER_SECOND as u64;
+            if fraction != 0 {
+                let fraction = format!("{:09}", fraction);
+                write!(f, ".{}", fraction.trim_end_matches('0'))?;
+            }
+            write!(f, "S")?;
+        }
+        Ok(())
+    }
+}
+
+/// `[nY][nM][nW][nD][T[nH][nM][nS]]` after the `P`, every number may be signed.
+fn parse_iso(text: &str) -> Option<Interval> {
+    let (date, time) = match text.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+    if date.is_empty() && time.unwrap_or_default().is_empty() {
+        return None;
+    }
+
+    let mut interval = Interval::default();
+    for (value, unit) in iso_components(date)? {
+        let value = whole(value)?;
+        match unit {
+            'Y' => interval.months = add(interval.months, value.checked_mul(12)?)?,
+            'M' => interval.months = add(interval.months, value)?,
+            'W' => interval.days = add(interval.days, value.checked_mul(7)?)?,
+            'D' => interval.days = add(interval.days, value)?,
+            _ => return None,
+        }
+    }
+    for (value, unit) in iso_components(time.unwrap_or_default())? {
+        let unit = match unit {
+            'H' => NANOS_PER_HOUR,
+            'M' => NANOS_PER_MINUTE,
+            'S' => NANOS_PER_SECOND,
+            _ => return None,
+        };
+        interval.nanos = interval.nanos.checked_add(scaled(value, unit)?)?;
+    }
+    Some(interval)
+}
+
+fn iso_components(text: &str) -> Option<Vec<(f64, char)>> {
+    let mut components = vec![];
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() || matches!(c, '.' | ',' | '-' | '+') {
+            number.push(if c == ',' { '.' } else { c });
+        } else {
+            components.push((number.parse::<f64>().ok()?, c.to_ascii_uppercase()));
+            number.clear();
+        }
+    }
+    number.is_empty().then_some(components)
+}
+
+/// Postgres, SQL standard and Oracle word forms.
+fn parse_words(text: &str) -> Option<Interval> {
+    let mut text = text.strip_prefix('@').unwrap_or(text).trim();
+    let ago = match text.strip_suffix("ago") {
+        Some(t) => {
+            text = t.trim_end();
+            true
+        }
+        None => false,
+    };
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut interval = Interval::default();
+    let mut tokens = text.split_whitespace().peekable();
+    while let Some(token) = tokens.next() {
+        if token.contains(':') {
+            interval.nanos = interval.nanos.checked_add(parse_clock(token)?)?;
+        } else if let Some(months) = parse_year_month(token) {
+            interval.months = add(interval.months, months)?;
+        } else {
+            let value = token.parse::<f64>().ok()?;
+            let unit = tokens.peek().and_then(|u| word_unit(u));
+            if unit.is_some() {
+                tokens.next();
+            }
+            match unit {
+                Some(WordUnit::Months(scale)) => {
+                    interval.months = add(interval.months, whole(value)?.checked_mul(scale)?)?
+                }
+                Some(WordUnit::Days(scale)) => {
+                    interval.days = add(interval.days, whole(value)?.checked_mul(scale)?)?
+                }
+                Some(WordUnit::Nanos(scale)) => {
+                    interval.nanos = interval.nanos.checked_add(scaled(value, scale)?)?
+                }
+                // a bare number is the day field of the SQL standard/Oracle form
+                None => interval.days = add(interval.days, whole(value)?)?,
+            }
+        }
+    }
+    Some(if ago { interval.negate() } else { interval })
+}
+
+enum WordUnit {
+    Months(i32),
+    Days(i32),
+    Nanos(i64),
+}
+
+fn word_unit(word: &str) -> Option<WordUnit> {
+    let unit = match word.to_ascii_lowercase().trim_end_matches(',') {
+        "millennium" | "millennia" | "millenniums" => WordUnit::Months(12_000),
+        "century" | "centuries" => WordUnit::Months(1200),
+        "decade" | "decades" => WordUnit::Months(120),
+        "year" | "years" | "yr" | "yrs" => WordUnit::Months(12),
+        "mon" | "mons" | "month" | "months" => WordUnit::Months(1),
+        "week" | "weeks" => WordUnit::Days(7),
+        "day" | "days" => WordUnit::Days(1),
+        "hour" | "hours" | "hr" | "hrs" => WordUnit::Nanos(NANOS_PER_HOUR),
+        "min" | "mins" | "minute" | "minutes" => WordUnit::Nanos(NANOS_PER_MINUTE),
+        "sec" | "secs" | "second" | "seconds" => WordUnit::Nanos(NANOS_PER_SECOND),
+        "millisecond" | "milliseconds" | "msec" | "msecs" => WordUnit::Nanos(1_000_000),
+        "microsecond" | "microseconds" | "usec" | "usecs" => WordUnit::Nanos(1_000),
+        _ => return None,
+    };
+    Some(unit)
+}
+
+/// `[+-]h:mm[:ss[.f]]`
+fn parse_clock(token: &str) -> Option<i64> {
+    let (negative, clock) = split_sign(token);
+    let mut parts = clock.split(':');
+    let hours = parts.next()?.parse::<i64>().ok()?;
+    let minutes = parts.next()?.parse::<i64>().ok()?;
+    let seconds = match parts.next() {
+        Some(s) => scaled(s.parse::<f64>().ok()?, NANOS_PER_SECOND)?,
+        None => 0,
+    };
+    if parts.next().is_some() || !(0..60).contains(&minutes) {
+        return None;
+    }
+    let nanos = hours
+        .checked_mul(NANOS_PER_HOUR)?
+        .checked_add(minutes * NANOS_PER_MINUTE + seconds)?;
+    Some(if negative { -nanos } else { nanos })
+}
+
+/// `[+-]y-m`, the SQL standard year-month field.
+fn parse_year_month(token: &str) -> Option<i32> {
+    let (negative, value) = split_sign(token);
+    let (years, months) = value.split_once('-')?;
+    let years = years.parse::<i32>().ok()?;
+    let months = months.parse::<i32>().ok()?;
+    let total = years.checked_mul(12)?.checked_add(months)?;
+    Some(if negative { -total } else { total })
+}
+
+fn split_sign(token: &str) -> (bool, &str) {
+    match token.strip_prefix('-') {
+        Some(t) => (true, t),
+        None => (false, token.strip_prefix('+').unwrap_or(token)),
+    }
+}
+
+fn whole(value: f64) -> Option<i32> {
+    (value.fract() == 0.0 && value.abs() <= i32::MAX as f64).then_some(value as i32)
+}
+
+fn scaled(value: f64, unit: i64) -> Option<i64> {
+    let nanos = (value * unit as f64).round();
+    (nanos.abs() < i64::MAX as f64).then_some(nanos as i64)
+}
+
+fn add(a: i32, b: i32) -> Option<i32> {
+    a.checked_add(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(months: i32, days: i32, nanos: i64) -> Interval {
+        Interval {
+            months,
+            days,
+            nanos,
+        }
+    }
+
+    #[test]
+    fn test_parse_postgres() {
+        let hms = 4 * NANOS_PER_HOUR + 5 * NANOS_PER_MINUTE + 6_500_000_000;
+        assert_eq!(
+            Interval::parse("1 year 2 mons 3 days 04:05:06.5"),
+            Ok(interval(14, 3, hms))
+        );
+        assert_eq!(
+            Interval::parse("-1 days +02:00:00"),
+            Ok(interval(0, -1, 2 * NANOS_PER_HOUR))
+        );
+        assert_eq!(
+            Interval::parse("@ 1 year 2 mons 4 hours 6.5 secs ago"),
+            Ok(interval(-14, 0, -(4 * NANOS_PER_HOUR + 6_500_000_000)))
+        );
+        assert_eq!(Interval::parse("00:00:00"), Ok(Interval::default()));
+    }
+
+    #[test]
+    fn test_parse_sql_standard() {
+        assert_eq!(
+            Interval::parse("+1-2 +3 +4:05:06"),
+            Ok(interval(
+                14,
+                3,
+                4 * NANOS_PER_HOUR + 5 * NANOS_PER_MINUTE + 6 * NANOS_PER_SECOND
+            ))
+        );
+        assert_eq!(Interval::parse("-01-02"), Ok(interval(-14, 0, 0)));
+        assert_eq!(
+            Interval::parse("+02 03:04:05.000000"),
+            Ok(interval(
+                0,
+                2,
+                3 * NANOS_PER_HOUR + 4 * NANOS_PER_MINUTE + 5 * NANOS_PER_SECOND
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_iso() {
+        let hms = 4 * NANOS_PER_HOUR + 5 * NANOS_PER_MINUTE + 6_500_000_000;
+        assert_eq!(
+            Interval::parse("P1Y2M3DT4H5M6.5S"),
+            Ok(interval(14, 3, hms))
+        );
+        assert_eq!(Interval::parse("P-1Y-2M"), Ok(interval(-14, 0, 0)));
+        assert_eq!(
+            Interval::parse("-PT5M"),
+            Ok(interval(0, 0, -5 * NANOS_PER_MINUTE))
+        );
+        assert_eq!(Interval::parse("P2W"), Ok(interval(0, 14, 0)));
+        assert!(Interval::parse("P").is_err());
+        assert!(Interval::parse("P1X").is_err());
+        assert!(Interval::parse("1 fortnight").is_err());
+        assert!(Interval::parse("").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        for text in [
+            "P1Y2M3DT4H5M6.5S",
+            "P-1Y-2M",
+            "PT-5M",
+            "P3D",
+            "PT0.000001S",
+            "PT0S",
+        ] {
+            assert_eq!(Interval::parse(text).unwrap().to_string(), text);
+        }
+        assert_eq!(
+            Interval::parse("1 year 2 mons -3 days 04:05:06")
+                .unwrap()
+                .to_string(),
+            "P1Y2M-3DT4H5M6S"
+        );
+    }
+
+    #[test]
+    fn test_human_units() {
+        assert_eq!(Interval::default().human_units(), "0s");
+        assert_eq!(
+            interval(14, 3, 5_400 * NANOS_PER_SECOND + NANOS_PER_SECOND / 2).human_units(),
+            "1y 2mo 3d 1h 30m 0.5s"
+        );
+        assert_eq!(
+            interval(0, 0, -90 * NANOS_PER_MINUTE).human_units(),
+            "-1h -30m"
+        );
+    }
+}
@@ -1,4 +1,11 @@
 pub mod dameng;
+pub mod epoch;
+pub mod interval;
+pub mod mysql;
+pub mod nu;
 pub mod odbc;
 pub mod pg;
+pub mod pg_array;
+pub mod sql;
+pub mod text;
 pub mod util;
@@ -0,0 +1,158 @@
+//! MySQL `ENUM` and `SET` columns reach ODBC as plain strings, a `SET` value being the
+//! comma-separated members, e.g. `read,write`. MySQL doesn't allow commas in members, so
+//! splitting on `,` is exact. With a [`EnumSetHint`] per column, `SET` cells become `Array`
+//! (in the Postgres text form, see [`crate::extension::pg_array::parse_pg_array`]) and `ENUM`
+//! cells stay `Text`, optionally validated against the allowed values.
+use crate::error::OdbcHelperError;
+use crate::executor::query::QueryResult;
+use crate::extension::odbc::{OdbcColumnItem, OdbcColumnType};
+use bytes::BytesMut;
+use std::collections::HashMap;
+
+/// How the cells of a MySQL `ENUM`/`SET` column are handled. An empty list of allowed values
+/// skips the validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnumSetHint {
+    Enum(Vec<String>),
+    Set(Vec<String>),
+}
+
+/// Apply `hints`, keyed by column name, to the text cells of `result`.
+/// A value not in the allowed list is an `OdbcHelperError::ValueNotAllowed`.
+pub fn apply_enum_set_hints(
+    result: &mut QueryResult,
+    hints: &HashMap<String, EnumSetHint>,
+) -> Result<(), OdbcHelperError> {
+    let hinted = result
+        .columns
+        .iter()
+        .enumerate()
+        .filter_map(|(index, c)| {
+            hints
+                .get(&c.name)
+                .map(|hint| (index, c.name.as_str(), hint))
+        })
+        .collect::<Vec<_>>();
+    for row in result.data.iter_mut() {
+        for (index, name, hint) in hinted.iter() {
+            if let Some(item) = row.get_mut(*index) {
+                apply_hint(item, hint).map_err(|value| OdbcHelperError::ValueNotAllowed {
+                    column: name.to_string(),
+                    value,
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `Err` holds the value that is not allowed.
+fn apply_hint(item: &mut OdbcColumnItem, hint: &EnumSetHint) -> Result<(), String> {
+    if !matches!(item.odbc_type, OdbcColumnType::Text | OdbcColumnType::WText) {
+        return Ok(());
+    }
+    let allowed =
+        |allowed: &[String], v: &str| allowed.is_empty() || allowed.iter().any(|a| a == v);
+    let value = match &item.value {
+        Some(value) => String::from_utf8_lossy(value).to_string(),
+        None => {
+            if let EnumSetHint::Set(_) = hint {
+                item.odbc_type = OdbcColumnType::Array;
+            }
+            return Ok(());
+        }
+    };
+    match hint {
+        EnumSetHint::Enum(values) => {
+            if !allowed(values, &value) {
+                return Err(value);
+            }
+        }
+        EnumSetHint::Set(values) => {
+            let members = value
+                .split(',')
+                .filter(|m| !m.is_empty())
+                .collect::<Vec<_>>();
+            if let Some(member) = members.iter().find(|m| !allowed(values, m)) {
+                return Err(member.to_string());
+            }
+            let elements = members
+                .iter()
+                .map(|m| format!("\"{}\"", m.replace('\\', "\\\\").replace('"', "\\\"")))
+                .collect::<Vec<_>>();
+            item.odbc_type = OdbcColumnType::Array;
+            item.value = Some(BytesMut::from(
+                format!("{{{}}}", elements.join(",")).as_str(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures;
+    use crate::extension::odbc::OdbcColumn;
+    use crate::extension::pg_array::parse_pg_array;
+    use crate::odbc_api::DataType;
+
+    fn result(values: &[Option<&str>]) -> QueryResult {
+        QueryResult {
+            columns: vec![OdbcColumn::new(
+                "perms".to_string(),
+                DataType::Char { length: 20 },
+                true,
+            )],
+            data: values
+                .iter()
+                .map(|v| vec![fixtures::item(OdbcColumnType::Text, *v)])
+                .collect(),
+        }
+    }
+
+    fn hints(hint: EnumSetHint) -> HashMap<String, EnumSetHint> {
+        HashMap::from([("perms".to_string(), hint)])
+    }
+
+    #[test]
+    fn test_set_hint() {
+        let mut result = result(&[Some("read,write"), Some(""), None]);
+        apply_enum_set_hints(&mut result, &hints(EnumSetHint::Set(vec![]))).unwrap();
+        assert!(result
+            .data
+            .iter()
+            .all(|row| row[0].odbc_type == OdbcColumnType::Array));
+
+        let members = |row: usize| {
+            let text = result.data[row][0].value_to_string().unwrap();
+            parse_pg_array(&text, OdbcColumnType::Text)
+                .unwrap()
+                .iter()
+                .map(|i| i.value_to_string().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(members(0), vec!["read", "write"]);
+        assert!(members(1).is_empty());
+        assert_eq!(result.data[2][0].value, None);
+
+        let allowed = EnumSetHint::Set(vec!["read".to_string()]);
+        let mut result = self::result(&[Some("read,write")]);
+        let err = apply_enum_set_hints(&mut result, &hints(allowed)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "column `perms` value `write` is not an allowed ENUM/SET value"
+        );
+    }
+
+    #[test]
+    fn test_enum_hint() {
+        let allowed = EnumSetHint::Enum(vec!["small".to_string(), "large".to_string()]);
+        let mut ok = result(&[Some("small"), None]);
+        apply_enum_set_hints(&mut ok, &hints(allowed.clone())).unwrap();
+        assert_eq!(ok.data[0][0].odbc_type, OdbcColumnType::Text);
+
+        let mut bad = result(&[Some("medium")]);
+        assert!(apply_enum_set_hints(&mut bad, &hints(allowed)).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! Conversion of result sets to nushell values, for plugins handing query results to nu.
+use crate::extension::odbc::{OdbcColumn, OdbcColumnItem, OdbcColumnType};
+use crate::extension::util::{parse_to_data_time, parse_to_date};
+use chrono::{DateTime, FixedOffset, TimeZone};
+use odbc_common::{Span, Value};
+use time::format_description::well_known::Rfc3339;
+
+/// The nu value of a cell, NULL is `Nothing`.
+///
+/// Integers become `Int` (a `U64` above `i64::MAX` stays a `String`), floats `Float`, `Bit`
+/// `Bool` and `Binary` `Binary`. `Date`, `Timestamp` and `TimestampTz` become `Date`, UTC when
+/// the value has no offset. Other types and values that fail to parse are their text as
+/// `String`.
+pub fn to_nu_value(item: &OdbcColumnItem, span: Span) -> Value {
+    let value = match &item.value {
+        Some(value) => value,
+        None => return Value::Nothing { span },
+    };
+    let utc = FixedOffset::east_opt(0).unwrap();
+    let typed = match item.odbc_type {
+        OdbcColumnType::Bit => item.bit_as_int().map(|v| Value::Bool { val: v == 1, span }),
+        OdbcColumnType::F32 | OdbcColumnType::F64 => {
+            item.as_f64().map(|val| Value::Float { val, span })
+        }
+        t if t.is_numeric() => item.as_i64().map(|val| Value::Int { val, span }),
+        OdbcColumnType::Binary => Some(Value::Binary {
+            val: value.to_vec(),
+            span,
+        }),
+        OdbcColumnType::Date => parse_to_date(value.clone())
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|naive| Value::Date {
+                val: utc.from_utc_datetime(&naive),
+                span,
+            }),
+        OdbcColumnType::Timestamp => {
+            parse_to_data_time(value.clone())
+                .ok()
+                .map(|naive| Value::Date {
+                    val: utc.from_utc_datetime(&naive),
+                    span,
+                })
+        }
+        OdbcColumnType::TimestampTz => item
+            .as_offset_date_time()
+            .and_then(|v| v.format(&Rfc3339).ok())
+            .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+            .map(|val| Value::Date { val, span }),
+        _ => None,
+    };
+    typed.unwrap_or_else(|| Value::String {
+        val: item.value_to_string().unwrap_or_default(),
+        span,
+    })
+}
+
+/// The result set as a `List` of `Record`s keyed by column name, the table a nushell plugin
+/// returns. Cells are converted with [`to_nu_value`].
+pub fn to_nu_table(columns: &[OdbcColumn], rows: Vec<Vec<OdbcColumnItem>>, span: Span) -> Value {
+    let cols = columns.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+    let vals = rows
+        .iter()
+        .map(|row| Value::Record {
+            cols: cols.clone(),
+            vals: row.iter().map(|item| to_nu_value(item, span)).collect(),
+            span,
+        })
+        .collect();
+    Value::List { vals, span }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures::item;
+    use crate::odbc_api::DataType;
+
+    #[test]
+    fn test_to_nu_table() {
+        let span = Span::test_data();
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::BigInt, false),
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 20 }, true),
+            OdbcColumn::new(
+                "created".to_string(),
+                DataType::Timestamp { precision: 3 },
+                true,
+            ),
+        ];
+        let rows = vec![vec![
+            item(OdbcColumnType::I64, Some("7")),
+            item(OdbcColumnType::Text, None),
+            item(OdbcColumnType::Timestamp, Some("2022-08-24 15:50:36.5")),
+        ]];
+
+        let table = to_nu_table(&columns, rows, span);
+        let records = match table {
+            Value::List { vals, .. } => vals,
+            other => panic!("expected a list, got {:?}", other),
+        };
+        let (cols, vals) = match &records[0] {
+            Value::Record { cols, vals, .. } => (cols, vals),
+            other => panic!("expected a record, got {:?}", other),
+        };
+        assert_eq!(cols, &vec!["id", "name", "created"]);
+        assert_eq!(vals[0], Value::Int { val: 7, span });
+        assert_eq!(vals[1], Value::Nothing { span });
+        match &vals[2] {
+            Value::Date { val, .. } => {
+                assert_eq!(val.to_rfc3339(), "2022-08-24T15:50:36.500+00:00")
+            }
+            other => panic!("expected a date, got {:?}", other),
+        }
+    }
+}
@@ -1,18 +1,37 @@
+use crate::error::OdbcHelperError;
 use crate::executor::database::Options;
+use crate::extension::interval::Interval;
+use crate::extension::util::{parse_to_data_time, parse_to_date, parse_to_time};
 use crate::odbc_api::{
     buffers::{AnySlice, BufferDesc},
-    sys::{Date, Time, Timestamp, NULL_DATA},
-    DataType,
+    handles::{SqlResult, Statement},
+    sys::{Date, Desc, Time, Timestamp, NULL_DATA},
+    ColumnDescription, DataType, ResultSetMetadata,
 };
 use crate::{Convert, TryConvert};
 use bytes::BytesMut;
-use std::cmp::min;
+use odbc_common::SqlValue;
+use std::cmp::{min, Ordering};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Clone)]
 pub struct OdbcColumn {
     pub name: String,
     pub data_type: DataType,
     pub nullable: bool,
+    // identity/auto-increment column, `None` when the driver doesn't report it.
+    pub auto_increment: Option<bool>,
+    // `SQL_DESC_UNSIGNED`, `None` when the driver doesn't report it.
+    pub unsigned: Option<bool>,
+    // `SQL_DESC_LENGTH`, e.g. n of a MySQL `BIT(n)`, `None` when the driver doesn't report it.
+    pub declared_length: Option<usize>,
+    // column comment, e.g. for generated documentation. ODBC has no `SQL_DESC_*` attribute for
+    // it, so `from_cursor` leaves it `None`, set it with `with_comment` (e.g. from the `REMARKS`
+    // of a `SQLColumns` catalog query).
+    pub comment: Option<String>,
 }
 
 impl OdbcColumn {
@@ -21,6 +40,785 @@ impl OdbcColumn {
             name,
             data_type,
             nullable,
+            auto_increment: None,
+            unsigned: None,
+            declared_length: None,
+            comment: None,
+        }
+    }
+
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Like [`OdbcColumn::new`] but rejects empty or whitespace-only names, which break exports
+    /// keyed by name such as JSON, with `OdbcHelperError::InvalidColumnName`.
+    pub fn try_new(
+        name: String,
+        data_type: DataType,
+        nullable: bool,
+    ) -> Result<Self, OdbcHelperError> {
+        if name.trim().is_empty() {
+            return Err(OdbcHelperError::InvalidColumnName(name));
+        }
+        Ok(Self::new(name, data_type, nullable))
+    }
+
+    /// Read the column `column_number` (starting at 1) from the statement's column descriptor,
+    /// including `SQL_DESC_AUTO_UNIQUE_VALUE`, `SQL_DESC_UNSIGNED` and `SQL_DESC_LENGTH` when the
+    /// driver supports them.
+    pub fn from_cursor<C: ResultSetMetadata>(
+        cursor: &mut C,
+        column_number: u16,
+    ) -> anyhow::Result<Self> {
+        let mut column_description = ColumnDescription::default();
+        cursor.describe_col(column_number, &mut column_description)?;
+        let mut column = OdbcColumn::new(
+            column_description.name_to_string()?,
+            column_description.data_type,
+            column_description.could_be_nullable(),
+        );
+
+        let stmt = cursor.as_stmt_ref();
+        // Safety: `SQL_DESC_AUTO_UNIQUE_VALUE` is a numeric attribute.
+        let auto_unique =
+            unsafe { stmt.numeric_col_attribute(Desc::AutoUniqueValue, column_number) };
+        column.auto_increment = match auto_unique {
+            SqlResult::Success(v) | SqlResult::SuccessWithInfo(v) => Some(v != 0),
+            _ => None,
+        };
+        // Safety: `SQL_DESC_UNSIGNED` is a numeric attribute.
+        let unsigned = unsafe { stmt.numeric_col_attribute(Desc::Unsigned, column_number) };
+        column.unsigned = match unsigned {
+            SqlResult::Success(v) | SqlResult::SuccessWithInfo(v) => Some(v != 0),
+            _ => None,
+        };
+        // Safety: `SQL_DESC_LENGTH` is a numeric attribute.
+        let length = unsafe { stmt.numeric_col_attribute(Desc::Length, column_number) };
+        column.declared_length = match length {
+            SqlResult::Success(v) | SqlResult::SuccessWithInfo(v) => usize::try_from(v).ok(),
+            _ => None,
+        };
+        Ok(column)
+    }
+
+    /// Whether the column looks like a SQL Server `rowversion`/`timestamp`:
+    /// a non-nullable `BINARY(8)`, see [`OdbcColumnItem::as_rowversion`].
+    pub fn is_rowversion(&self) -> bool {
+        !self.nullable && self.data_type == DataType::Binary { length: 8 }
+    }
+
+    /// A `BIGINT` the driver reports as unsigned, e.g. MySQL `BIGINT UNSIGNED`. Its values may
+    /// exceed `i64::MAX`, so it's fetched as text and converted with [`to_unsigned_bigint`].
+    pub fn is_unsigned_bigint(&self) -> bool {
+        self.unsigned == Some(true) && self.data_type == DataType::BigInt
+    }
+
+    /// n of a multi-bit `BIT(n)` column (n > 1, e.g. MySQL `BIT(8)` flags), which is a number
+    /// rather than a boolean. It's fetched as binary and converted with [`bits_to_integer`].
+    pub fn multi_bit_width(&self) -> Option<usize> {
+        match (self.data_type, self.declared_length) {
+            (DataType::Bit, Some(n)) if n > 1 => Some(n),
+            _ => None,
+        }
+    }
+}
+
+/// `name TYPE [NOT NULL]`, e.g. `id INTEGER NOT NULL` or `note VARCHAR(255)`.
+impl fmt::Display for OdbcColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, type_display(&self.data_type))?;
+        if !self.nullable {
+            write!(f, " NOT NULL")?;
+        }
+        Ok(())
+    }
+}
+
+/// Give every column a usable, unique name: unnamed columns (e.g. expressions) become
+/// `column_N` with N the 1-based ordinal, a repeated name gets the `_N` suffix of its ordinal,
+/// e.g. `id`, `id_3`. Names are compared case-sensitively.
+pub fn normalize_names(columns: &mut [OdbcColumn]) {
+    let mut seen = HashSet::with_capacity(columns.len());
+    for (index, column) in columns.iter_mut().enumerate() {
+        let ordinal = index + 1;
+        if column.name.trim().is_empty() {
+            column.name = format!("column_{}", ordinal);
+        }
+        let mut name = column.name.clone();
+        let mut suffix = ordinal;
+        while !seen.insert(name.clone()) {
+            name = format!("{}_{}", column.name, suffix);
+            suffix += 1;
+        }
+        column.name = name;
+    }
+}
+
+/// Column name to ordinal lookup, built once from the result set columns.
+/// When a name is duplicated the first column wins.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnIndex {
+    ordinals: HashMap<String, usize>,
+}
+
+impl ColumnIndex {
+    pub fn new(columns: &[OdbcColumn]) -> Self {
+        let mut ordinals = HashMap::with_capacity(columns.len());
+        for (index, column) in columns.iter().enumerate() {
+            ordinals.entry(column.name.clone()).or_insert(index);
+        }
+        Self { ordinals }
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.ordinals.get(name).copied()
+    }
+
+    /// Get the cell of `row` for the column `name`.
+    pub fn get<'a>(&self, row: &'a [OdbcColumnItem], name: &str) -> Option<&'a OdbcColumnItem> {
+        self.index_of(name).and_then(|index| row.get(index))
+    }
+}
+
+/// SQL-ish display name of a `DataType`, used for headers and schema output.
+///
+/// # Example
+///
+/// ```rust
+/// use odbc_common::odbc_api::DataType;
+/// use odbc_api_helper::extension::odbc::type_display;
+///
+/// assert_eq!(type_display(&DataType::Varchar { length: 255 }), "VARCHAR(255)");
+/// assert_eq!(type_display(&DataType::Decimal { precision: 18, scale: 4 }), "DECIMAL(18,4)");
+/// assert_eq!(type_display(&DataType::Integer), "INTEGER");
+/// assert_eq!(type_display(&DataType::Timestamp { precision: 0 }), "TIMESTAMP");
+/// assert_eq!(type_display(&DataType::Timestamp { precision: 6 }), "TIMESTAMP(6)");
+/// ```
+pub fn type_display(dt: &DataType) -> String {
+    let with_precision = |name: &str, precision: i16| {
+        if precision > 0 {
+            format!("{}({})", name, precision)
+        } else {
+            name.to_string()
+        }
+    };
+
+    match dt {
+        DataType::Unknown => "UNKNOWN".to_string(),
+        DataType::Char { length } => format!("CHAR({})", length),
+        DataType::WChar { length } => format!("NCHAR({})", length),
+        DataType::Numeric { precision, scale } => format!("NUMERIC({},{})", precision, scale),
+        DataType::Decimal { precision, scale } => format!("DECIMAL({},{})", precision, scale),
+        DataType::Integer => "INTEGER".to_string(),
+        DataType::SmallInt => "SMALLINT".to_string(),
+        DataType::Float { precision } => format!("FLOAT({})", precision),
+        DataType::Real => "REAL".to_string(),
+        DataType::Double => "DOUBLE".to_string(),
+        DataType::Varchar { length } => format!("VARCHAR({})", length),
+        DataType::WVarchar { length } => format!("NVARCHAR({})", length),
+        DataType::LongVarchar { .. } => "TEXT".to_string(),
+        DataType::LongVarbinary { .. } => "BLOB".to_string(),
+        DataType::Date => "DATE".to_string(),
+        DataType::Time { precision } => with_precision("TIME", *precision),
+        DataType::Timestamp { precision } => with_precision("TIMESTAMP", *precision),
+        DataType::BigInt => "BIGINT".to_string(),
+        DataType::TinyInt => "TINYINT".to_string(),
+        DataType::Bit => "BIT".to_string(),
+        DataType::Varbinary { length } => format!("VARBINARY({})", length),
+        DataType::Binary { length } => format!("BINARY({})", length),
+        DataType::Other { data_type, .. } => format!("OTHER({})", data_type.0),
+    }
+}
+
+/// `SQL_SS_TIMESTAMPOFFSET`, the SQL Server `DATETIMEOFFSET` type.
+pub const SQL_SS_TIMESTAMPOFFSET: i16 = -155;
+
+/// Text length of `DATETIMEOFFSET`, e.g. `2022-08-24 15:50:36.1234567 +08:00`.
+const TIMESTAMP_TZ_LEN: usize = 34;
+
+/// Text length reserved for intervals, e.g. `+1000000 23:59:59.999999999`.
+const INTERVAL_LEN: usize = 64;
+
+/// Text length of an unsigned 64-bit integer, `18446744073709551615`.
+const U64_LEN: usize = 20;
+
+/// Whether a column carries a timezone offset, bound as text and converted to `TimestampTz`.
+pub fn is_timestamp_tz(data_type: &DataType) -> bool {
+    data_type.data_type().0 == SQL_SS_TIMESTAMPOFFSET
+}
+
+/// Parse a timestamp with offset as the drivers report it, the space before the offset
+/// and the fraction are optional, e.g. `2022-08-24 15:50:36.1234567 +08:00`.
+pub fn parse_timestamp_tz(text: &str) -> Result<time::OffsetDateTime, time::error::Parse> {
+    let format = time::macros::format_description!(
+        "[year]-[month]-[day] [hour]:[minute]:[second][optional [.[subsecond]]][optional [ ]][offset_hour sign:mandatory]:[offset_minute]"
+    );
+    time::OffsetDateTime::parse(text.trim(), format)
+}
+
+/// Whether a column is one of the ODBC `SQL_INTERVAL_*` types, bound as text and converted
+/// to `Interval`.
+pub fn is_interval(data_type: &DataType) -> bool {
+    // SQL_INTERVAL_YEAR (101) to SQL_INTERVAL_MINUTE_TO_SECOND (113)
+    (101..=113).contains(&data_type.data_type().0)
+}
+
+/// Whether a column is `BINARY`, `VARBINARY` or `LONGVARBINARY`.
+pub fn is_binary(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Binary { .. } | DataType::Varbinary { .. } | DataType::LongVarbinary { .. }
+    )
+}
+
+/// Decode the text cells of a binary column as hex, with or without a `0x` prefix, into
+/// `Binary`. Odd-length or non-hex text is an `OdbcHelperError::TypeConversionError`.
+pub fn hex_text_to_binary(items: &mut [OdbcColumnItem]) -> Result<(), OdbcHelperError> {
+    for item in items.iter_mut() {
+        if !matches!(item.odbc_type, OdbcColumnType::Text | OdbcColumnType::WText) {
+            continue;
+        }
+        if let Some(value) = item.value.as_mut() {
+            let text = String::from_utf8_lossy(value);
+            let hex = text.trim();
+            let hex = hex
+                .strip_prefix("0x")
+                .or_else(|| hex.strip_prefix("0X"))
+                .unwrap_or(hex);
+            if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(OdbcHelperError::TypeConversionError(format!(
+                    "invalid hex binary `{}`",
+                    text
+                )));
+            }
+            let bytes = (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                .collect::<Vec<u8>>();
+            *value = BytesMut::from(bytes.as_slice());
+        }
+        item.odbc_type = OdbcColumnType::Binary;
+    }
+    Ok(())
+}
+
+/// Convert the text cells of an interval column to `Interval` with the canonical ISO-8601 text.
+pub fn to_interval(items: &mut [OdbcColumnItem]) -> Result<(), OdbcHelperError> {
+    for item in items.iter_mut() {
+        if !matches!(item.odbc_type, OdbcColumnType::Text | OdbcColumnType::WText) {
+            continue;
+        }
+        if let Some(value) = item.value.as_mut() {
+            let interval = Interval::parse(&String::from_utf8_lossy(value))
+                .map_err(OdbcHelperError::TypeConversionError)?;
+            *value = BytesMut::from(interval.to_string().as_bytes());
+        }
+        item.odbc_type = OdbcColumnType::Interval;
+    }
+    Ok(())
+}
+
+/// `DataType` without its length/precision, the key of a [`CoercionPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataTypeKind {
+    Unknown,
+    Char,
+    WChar,
+    Numeric,
+    Decimal,
+    Integer,
+    SmallInt,
+    Float,
+    Real,
+    Double,
+    Varchar,
+    WVarchar,
+    LongVarchar,
+    LongVarbinary,
+    Date,
+    Time,
+    Timestamp,
+    BigInt,
+    TinyInt,
+    Bit,
+    Varbinary,
+    Binary,
+    Other,
+}
+
+impl From<&DataType> for DataTypeKind {
+    fn from(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Unknown => DataTypeKind::Unknown,
+            DataType::Char { .. } => DataTypeKind::Char,
+            DataType::WChar { .. } => DataTypeKind::WChar,
+            DataType::Numeric { .. } => DataTypeKind::Numeric,
+            DataType::Decimal { .. } => DataTypeKind::Decimal,
+            DataType::Integer => DataTypeKind::Integer,
+            DataType::SmallInt => DataTypeKind::SmallInt,
+            DataType::Float { .. } => DataTypeKind::Float,
+            DataType::Real => DataTypeKind::Real,
+            DataType::Double => DataTypeKind::Double,
+            DataType::Varchar { .. } => DataTypeKind::Varchar,
+            DataType::WVarchar { .. } => DataTypeKind::WVarchar,
+            DataType::LongVarchar { .. } => DataTypeKind::LongVarchar,
+            DataType::LongVarbinary { .. } => DataTypeKind::LongVarbinary,
+            DataType::Date => DataTypeKind::Date,
+            DataType::Time { .. } => DataTypeKind::Time,
+            DataType::Timestamp { .. } => DataTypeKind::Timestamp,
+            DataType::BigInt => DataTypeKind::BigInt,
+            DataType::TinyInt => DataTypeKind::TinyInt,
+            DataType::Bit => DataTypeKind::Bit,
+            DataType::Varbinary { .. } => DataTypeKind::Varbinary,
+            DataType::Binary { .. } => DataTypeKind::Binary,
+            DataType::Other { .. } => DataTypeKind::Other,
+        }
+    }
+}
+
+/// Type the converted cells of every column of a `DataTypeKind` are coerced to, e.g.
+/// `Bit` => `I32` or `Decimal` => `Text`. Applied after conversion, the fetch buffers don't change.
+pub type CoercionPolicy = HashMap<DataTypeKind, OdbcColumnType>;
+
+/// Coerce the cells of one column to `target`, see [`CoercionPolicy`].
+///
+/// Any type can become `Text`/`WText`. Numbers, `Bit` and numeric text convert between each
+/// other, integers must fit the target width and `Bit` is 0/1. Other conversions are an error.
+pub fn coerce_items(
+    items: &mut [OdbcColumnItem],
+    target: OdbcColumnType,
+) -> Result<(), OdbcHelperError> {
+    for item in items.iter_mut() {
+        if item.odbc_type == target {
+            continue;
+        }
+        if item.value.is_some() {
+            item.value = Some(coerce_value(item, target)?);
+        }
+        item.odbc_type = target;
+    }
+    Ok(())
+}
+
+fn coerce_value(
+    item: &OdbcColumnItem,
+    target: OdbcColumnType,
+) -> Result<BytesMut, OdbcHelperError> {
+    let text = item.value_to_string().unwrap_or_default();
+    let error = || {
+        OdbcHelperError::TypeConversionError(format!(
+            "can't coerce {:?} `{}` to {:?}",
+            item.odbc_type, text, target
+        ))
+    };
+    let number = || match item.odbc_type {
+        OdbcColumnType::Bit => Some(if text == "true" { 1.0 } else { 0.0 }),
+        OdbcColumnType::Text | OdbcColumnType::WText => text.trim().parse::<f64>().ok(),
+        t if t.is_numeric() => item.as_f64(),
+        _ => None,
+    };
+    let integer = || {
+        if let Some(v) = item.as_i64().or_else(|| match item.odbc_type {
+            OdbcColumnType::Text | OdbcColumnType::WText => text.trim().parse::<i64>().ok(),
+            _ => None,
+        }) {
+            return Ok(v);
+        }
+        match number() {
+            Some(v) if v.fract() == 0.0 && v >= i64::MIN as f64 && v <= i64::MAX as f64 => {
+                Ok(v as i64)
+            }
+            _ => Err(error()),
+        }
+    };
+
+    let bytes = match target {
+        OdbcColumnType::Text | OdbcColumnType::WText => BytesMut::from(text.as_bytes()),
+        OdbcColumnType::I8 | OdbcColumnType::I16 | OdbcColumnType::I32 | OdbcColumnType::I64 => {
+            let v = integer()?;
+            let (min, max) = match target {
+                OdbcColumnType::I8 => (i8::MIN as i64, i8::MAX as i64),
+                OdbcColumnType::I16 => (i16::MIN as i64, i16::MAX as i64),
+                OdbcColumnType::I32 => (i32::MIN as i64, i32::MAX as i64),
+                _ => (i64::MIN, i64::MAX),
+            };
+            if v < min || v > max {
+                return Err(error());
+            }
+            BytesMut::from(v.to_string().as_bytes())
+        }
+        OdbcColumnType::U8 => {
+            let v = u8::try_from(integer()?).map_err(|_| error())?;
+            BytesMut::from(&[v][..])
+        }
+        OdbcColumnType::U64 => {
+            let v = match item.odbc_type {
+                OdbcColumnType::Text | OdbcColumnType::WText => text.trim().parse::<u64>().ok(),
+                _ => None,
+            };
+            let v = match v {
+                Some(v) => v,
+                None => u64::try_from(integer()?).map_err(|_| error())?,
+            };
+            BytesMut::from(v.to_string().as_bytes())
+        }
+        OdbcColumnType::F32 | OdbcColumnType::F64 => {
+            BytesMut::from(number().ok_or_else(error)?.to_string().as_bytes())
+        }
+        OdbcColumnType::Bit => {
+            let v = match text.as_str() {
+                "true" => true,
+                "false" => false,
+                _ => number().ok_or_else(error)? != 0.0,
+            };
+            BytesMut::from(if v { "true" } else { "false" })
+        }
+        _ => return Err(error()),
+    };
+    Ok(bytes)
+}
+
+/// Remove a leading UTF-8 BOM from `Text`/`WText` cells, see `Options::strip_bom`.
+pub fn strip_bom(items: &mut [OdbcColumnItem]) {
+    const BOM: &[u8] = "\u{FEFF}".as_bytes();
+    for item in items.iter_mut() {
+        if !matches!(item.odbc_type, OdbcColumnType::Text | OdbcColumnType::WText) {
+            continue;
+        }
+        if let Some(value) = item.value.as_mut() {
+            if value.starts_with(BOM) {
+                let _ = value.split_to(BOM.len());
+            }
+        }
+    }
+}
+
+/// Convert `Timestamp` cells with a zero time of day, fraction included, to `Date`.
+/// Cells that don't parse as a timestamp are left as they are.
+pub fn collapse_midnight_timestamps(items: &mut [OdbcColumnItem]) {
+    for item in items.iter_mut() {
+        if item.odbc_type != OdbcColumnType::Timestamp {
+            continue;
+        }
+        let date_time = match item.value.clone().map(parse_to_data_time) {
+            Some(Ok(date_time)) => date_time,
+            _ => continue,
+        };
+        if date_time.time() == chrono::NaiveTime::MIN {
+            item.odbc_type = OdbcColumnType::Date;
+            item.value = Some(BytesMut::from(date_time.date().to_string().as_bytes()));
+        }
+    }
+}
+
+/// Convert the cells of an unsigned `BIGINT` column, see [`OdbcColumn::is_unsigned_bigint`], to
+/// `U64`. Text cells are parsed, `I64` cells hold the bits of the unsigned value (an 8 bytes
+/// buffer read as signed) and are reinterpreted, so e.g. `-1` becomes `18446744073709551615`.
+pub fn to_unsigned_bigint(items: &mut [OdbcColumnItem]) -> Result<(), OdbcHelperError> {
+    for item in items
+        .iter_mut()
+        .filter(|item| item.odbc_type == OdbcColumnType::I64)
+    {
+        if let Some(v) = item.as_i64() {
+            item.value = Some(BytesMut::from((v as u64).to_string().as_str()));
+        }
+        item.odbc_type = OdbcColumnType::U64;
+    }
+    coerce_items(items, OdbcColumnType::U64)
+}
+
+/// Widen `I8`, `I16`, `I32` and `U8` cells to `I64`, e.g. to sum or compare integer columns of
+/// mixed widths. `U8` is read as its unsigned byte, so 255 stays 255. `U64` is left as is, its
+/// range doesn't fit any signed type.
+pub fn widen_to_i64(items: &mut [OdbcColumnItem]) {
+    for item in items.iter_mut() {
+        if !matches!(
+            item.odbc_type,
+            OdbcColumnType::I8 | OdbcColumnType::I16 | OdbcColumnType::I32 | OdbcColumnType::U8
+        ) {
+            continue;
+        }
+        if item.value.is_some() {
+            item.value = item
+                .as_i64()
+                .map(|v| BytesMut::from(v.to_string().as_str()));
+        }
+        item.odbc_type = OdbcColumnType::I64;
+    }
+}
+
+/// Convert `U8` cells of a `BIT` column to `Bit`, for drivers delivering booleans as 0/1 bytes.
+/// Any non-zero byte is true.
+pub fn to_bit(items: &mut [OdbcColumnItem]) {
+    for item in items.iter_mut() {
+        if item.odbc_type != OdbcColumnType::U8 {
+            continue;
+        }
+        if let Some(value) = item.value.as_mut() {
+            let bit = value.iter().any(|b| *b != 0);
+            *value = BytesMut::from(if bit { "true" } else { "false" });
+        }
+        item.odbc_type = OdbcColumnType::Bit;
+    }
+}
+
+/// Convert the big-endian `Binary` cells of a `BIT(n)` column, see
+/// [`OdbcColumn::multi_bit_width`], to `I64`, or `U64` when `n` is 64. More than 8 bytes is an
+/// `OdbcHelperError::TypeConversionError`.
+pub fn bits_to_integer(items: &mut [OdbcColumnItem], n: usize) -> Result<(), OdbcHelperError> {
+    let odbc_type = if n >= 64 {
+        OdbcColumnType::U64
+    } else {
+        OdbcColumnType::I64
+    };
+    for item in items
+        .iter_mut()
+        .filter(|item| item.odbc_type == OdbcColumnType::Binary)
+    {
+        if let Some(value) = item.value.as_mut() {
+            if value.len() > 8 {
+                return Err(OdbcHelperError::TypeConversionError(format!(
+                    "BIT({}) of {} bytes",
+                    n,
+                    value.len()
+                )));
+            }
+            let v = value.iter().fold(0u64, |v, b| v << 8 | *b as u64);
+            *value = BytesMut::from(v.to_string().as_str());
+        }
+        item.odbc_type = odbc_type;
+    }
+    Ok(())
+}
+
+/// Range of `TINYINT` in the source database, e.g. signed on SQL Server and DM8,
+/// unsigned on MySQL `TINYINT UNSIGNED`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TinyIntSign {
+    /// `-128..=127`
+    Signed,
+    /// `0..=255`
+    Unsigned,
+}
+
+/// Convert `I8`/`U8` cells of a `TINYINT` column to `I16`, reading the byte with `sign`.
+///
+/// `I16` holds both ranges losslessly, so columns from different databases aggregate together.
+/// A byte delivered with the other sign is reinterpreted, e.g. an `I8` of `-56` from an unsigned
+/// source is `200`, a `U8` of `206` from a signed source is `-50`.
+pub fn normalize_tinyint(items: &mut [OdbcColumnItem], sign: TinyIntSign) {
+    for item in items.iter_mut() {
+        let byte = match item.odbc_type {
+            OdbcColumnType::U8 => item.as_i64().map(|v| v as u8),
+            OdbcColumnType::I8 => item.as_i64().map(|v| v as i8 as u8),
+            _ => continue,
+        };
+        if let Some(byte) = byte {
+            let value = match sign {
+                TinyIntSign::Signed => byte as i8 as i16,
+                TinyIntSign::Unsigned => byte as i16,
+            };
+            item.value = Some(BytesMut::from(value.to_string().as_bytes()));
+        }
+        item.odbc_type = OdbcColumnType::I16;
+    }
+}
+
+/// Whether cells of `odbc_type` are what a column of `data_type` is converted to. Text buffers
+/// fit every non-binary type, they are the fallback of several bindings, e.g. unsigned
+/// `BIGINT`, and `Unknown`/`Other` columns accept any cells.
+pub fn is_expected_kind(data_type: &DataType, odbc_type: OdbcColumnType) -> bool {
+    use OdbcColumnType as T;
+    let text = matches!(odbc_type, T::Text | T::WText);
+    let integer = matches!(odbc_type, T::I8 | T::I16 | T::I32 | T::I64 | T::U8);
+    let float = matches!(odbc_type, T::F32 | T::F64);
+    match data_type {
+        DataType::TinyInt | DataType::SmallInt | DataType::Integer | DataType::BigInt => {
+            integer || text
+        }
+        DataType::Numeric { .. } | DataType::Decimal { .. } => integer || float || text,
+        DataType::Float { .. } | DataType::Real | DataType::Double => float || text,
+        DataType::Date => odbc_type == T::Date || text,
+        // `TIME` past `24:00:00` becomes an `Interval`
+        DataType::Time { .. } => matches!(odbc_type, T::Time | T::Interval) || text,
+        DataType::Timestamp { .. } => odbc_type == T::Timestamp || text,
+        // `BIT(n)` binds a binary buffer, see `OdbcColumn::multi_bit_width`
+        DataType::Bit => matches!(odbc_type, T::Bit | T::Binary) || text,
+        DataType::Binary { .. } | DataType::Varbinary { .. } | DataType::LongVarbinary { .. } => {
+            odbc_type == T::Binary
+        }
+        DataType::Char { .. }
+        | DataType::WChar { .. }
+        | DataType::Varchar { .. }
+        | DataType::WVarchar { .. }
+        | DataType::LongVarchar { .. } => text,
+        DataType::Unknown | DataType::Other { .. } => true,
+    }
+}
+
+/// Check the converted cells of `column` against its `DataType`, see [`is_expected_kind`].
+/// The first mismatching cell is an `OdbcHelperError::BufferKindMismatch`.
+pub fn check_buffer_kind(
+    items: &[OdbcColumnItem],
+    column: &OdbcColumn,
+) -> Result<(), OdbcHelperError> {
+    match items
+        .iter()
+        .find(|item| !is_expected_kind(&column.data_type, item.odbc_type))
+    {
+        Some(item) => Err(OdbcHelperError::BufferKindMismatch {
+            column: column.name.clone(),
+            expected: format!("{:?}", column.data_type),
+            got: item.odbc_type,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The scale declared by `data_type`, `None` for types without one.
+pub fn declared_scale(data_type: &DataType) -> Option<i16> {
+    match data_type {
+        DataType::Decimal { scale, .. } | DataType::Numeric { scale, .. } => Some(*scale),
+        _ => None,
+    }
+}
+
+/// The fractional second digits declared by a `TIME(p)`/`TIMESTAMP(p)` column, `None` for other
+/// types.
+pub fn fraction_digits(data_type: &DataType) -> Option<i16> {
+    match data_type {
+        DataType::Time { precision } | DataType::Timestamp { precision } => Some(*precision),
+        _ => None,
+    }
+}
+
+/// A float cell changed by [`round_to_scale`], `row` is its index in the rounded items.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundedCell {
+    pub row: usize,
+    pub original: f64,
+    pub rounded: f64,
+}
+
+impl RoundedCell {
+    pub fn delta(&self) -> f64 {
+        (self.rounded - self.original).abs()
+    }
+}
+
+/// Round the `F32`/`F64` cells to `scale` decimal places, e.g. `0.30000000000000004` to `0.3`
+/// with scale 2. A negative scale rounds to an integer, non-finite values are kept.
+/// Returns the cells whose value changed.
+pub fn round_to_scale(items: &mut [OdbcColumnItem], scale: i16) -> Vec<RoundedCell> {
+    let scale = scale.max(0) as usize;
+    let mut changed = vec![];
+    for (row, item) in items.iter_mut().enumerate() {
+        if !matches!(item.odbc_type, OdbcColumnType::F32 | OdbcColumnType::F64) {
+            continue;
+        }
+        let (original, rounded) = match item.as_f64() {
+            Some(v) if v.is_finite() => (v, format!("{:.*}", scale, v).parse::<f64>()),
+            _ => continue,
+        };
+        if let Ok(rounded) = rounded {
+            item.value = Some(BytesMut::from(rounded.to_string().as_bytes()));
+            if rounded != original {
+                changed.push(RoundedCell {
+                    row,
+                    original,
+                    rounded,
+                });
+            }
+        }
+    }
+    changed
+}
+
+/// Cells rounded by more than a threshold while fetching with `Options::round_floats_to_scale`,
+/// e.g. to catch a `DECIMAL` scale too small for the stored amounts. Set it as
+/// `Options::conversion_report` and read it after the fetch, clones share the same records.
+#[derive(Debug, Clone, Default)]
+pub struct ConversionReport {
+    threshold: f64,
+    rounded: Arc<Mutex<Vec<(String, RoundedCell)>>>,
+}
+
+impl ConversionReport {
+    /// Record cells whose rounding changed them by more than `threshold`.
+    pub fn new(threshold: f64) -> Self {
+        ConversionReport {
+            threshold,
+            ..Default::default()
+        }
+    }
+
+    /// Record the `cells` of `column` exceeding the threshold, `first_row` is added to their
+    /// row so it counts from the start of the result set.
+    pub fn record_rounded(&self, column: &str, first_row: usize, cells: &[RoundedCell]) {
+        let mut rounded = self.rounded.lock().unwrap_or_else(|e| e.into_inner());
+        for cell in cells.iter().filter(|c| c.delta() > self.threshold) {
+            let cell = RoundedCell {
+                row: first_row + cell.row,
+                ..*cell
+            };
+            rounded.push((column.to_string(), cell));
+        }
+    }
+
+    /// The recorded `(column, cell)` pairs in fetch order.
+    pub fn rounded(&self) -> Vec<(String, RoundedCell)> {
+        self.rounded
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+/// Convert the text cells of a timezone-aware column to `TimestampTz` with the canonical
+/// `[year]-[month]-[day] [hour]:[minute]:[second].[subsecond]+hh:mm` text.
+pub fn to_timestamp_tz(items: &mut [OdbcColumnItem]) -> Result<(), OdbcHelperError> {
+    let format = time::macros::format_description!(
+        "[year]-[month]-[day] [hour]:[minute]:[second].[subsecond][offset_hour sign:mandatory]:[offset_minute]"
+    );
+    for item in items.iter_mut() {
+        if !matches!(item.odbc_type, OdbcColumnType::Text | OdbcColumnType::WText) {
+            continue;
+        }
+        if let Some(value) = item.value.as_mut() {
+            let text = String::from_utf8_lossy(value).to_string();
+            let conversion_error = |e: &dyn fmt::Display| {
+                OdbcHelperError::TypeConversionError(format!("TimestampTz `{}`:{}", text, e))
+            };
+            let date_time = parse_timestamp_tz(&text).map_err(|e| conversion_error(&e))?;
+            let canonical = date_time.format(format).map_err(|e| conversion_error(&e))?;
+            *value = BytesMut::from(canonical.as_bytes());
+        }
+        item.odbc_type = OdbcColumnType::TimestampTz;
+    }
+    Ok(())
+}
+
+/// Buffer kind bound for text columns, see `Options::text_buffers`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextBufferPolicy {
+    #[default]
+    AlwaysWide,
+    /// Narrow buffers for the narrow types `Char`, `Varchar` and `LongVarchar`, wide buffers for
+    /// everything else.
+    ByDataType,
+}
+
+impl TextBufferPolicy {
+    fn text_desc(&self, data_type: &DataType, max_str_len: usize) -> BufferDesc {
+        let narrow = matches!(
+            data_type,
+            DataType::Char { .. } | DataType::Varchar { .. } | DataType::LongVarchar { .. }
+        );
+        match self {
+            TextBufferPolicy::ByDataType if narrow => BufferDesc::Text { max_str_len },
+            _ => BufferDesc::WText { max_str_len },
         }
     }
 }
@@ -31,6 +829,27 @@ impl TryConvert<BufferDesc> for (&OdbcColumn, &Options) {
     fn try_convert(self) -> Result<BufferDesc, Self::Error> {
         let c = self.0;
         let option = self.1;
+        if is_timestamp_tz(&c.data_type) {
+            return Ok(BufferDesc::Text {
+                max_str_len: c.data_type.column_size().max(TIMESTAMP_TZ_LEN),
+            });
+        }
+        if is_interval(&c.data_type) {
+            return Ok(BufferDesc::Text {
+                max_str_len: c.data_type.column_size().max(INTERVAL_LEN),
+            });
+        }
+        // `BufferDesc` has no unsigned 64-bit buffer and values above `i64::MAX` don't fit `I64`
+        if c.is_unsigned_bigint() {
+            return Ok(BufferDesc::Text {
+                max_str_len: U64_LEN,
+            });
+        }
+        if let Some(n) = c.multi_bit_width() {
+            return Ok(BufferDesc::Binary {
+                length: n.div_ceil(8),
+            });
+        }
         let mut desc = BufferDesc::from_data_type(c.data_type, c.nullable)
             .ok_or_else(|| format!("covert DataType:{:?} to BufferDesc error", c.data_type))?;
 
@@ -40,15 +859,10 @@ impl TryConvert<BufferDesc> for (&OdbcColumn, &Options) {
         match desc {
             // TODO Notice: The kind of `BufferDesc::Text` mix up varchar or text type
             // Need to distinguish between text type or varchar type
-            BufferDesc::Text { max_str_len } => {
-                desc = BufferDesc::WText {
-                    max_str_len: min(max_str_len, option.max_str_len),
-                };
-            }
-            BufferDesc::WText { max_str_len } => {
-                desc = BufferDesc::WText {
-                    max_str_len: min(max_str_len, option.max_str_len),
-                };
+            BufferDesc::Text { max_str_len } | BufferDesc::WText { max_str_len } => {
+                desc = option
+                    .text_buffers
+                    .text_desc(&c.data_type, min(max_str_len, option.max_str_len));
             }
             BufferDesc::Binary { length } => {
                 desc = BufferDesc::Binary {
@@ -62,13 +876,13 @@ impl TryConvert<BufferDesc> for (&OdbcColumn, &Options) {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct OdbcColumnItem {
     pub odbc_type: OdbcColumnType,
     pub value: Option<BytesMut>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum OdbcColumnType {
     Text,
     WText,
@@ -84,434 +898,1298 @@ pub enum OdbcColumnType {
     I64,
     U8,
     Bit,
+    /// Postgres array in its text form, e.g. `{1,2,NULL}`, see [`crate::extension::pg_array`].
+    Array,
+    /// JSON document text, see [`crate::executor::query::QueryResult::tag_json_columns`].
+    Json,
+    /// Timestamp with a timezone offset, e.g. `2022-08-24 15:50:36.5+08:00`.
+    /// See [`is_timestamp_tz`] and [`OdbcColumnItem::as_offset_date_time`].
+    TimestampTz,
+    /// SQL `INTERVAL` in the canonical ISO-8601 form, e.g. `P1Y2M3DT4H`.
+    /// See [`is_interval`] and [`OdbcColumnItem::as_interval`].
+    Interval,
+    /// Unsigned 64-bit integer text, for `BIGINT UNSIGNED` columns, see [`to_unsigned_bigint`].
+    U64,
 }
 
-impl ToString for OdbcColumnItem {
-    fn to_string(&self) -> String {
-        format!("{:?}", self)
+impl OdbcColumnType {
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            OdbcColumnType::F64
+                | OdbcColumnType::F32
+                | OdbcColumnType::I8
+                | OdbcColumnType::I16
+                | OdbcColumnType::I32
+                | OdbcColumnType::I64
+                | OdbcColumnType::U8
+                | OdbcColumnType::U64
+        )
     }
 }
 
-impl Convert<Vec<OdbcColumnItem>> for AnySlice<'_> {
-    fn convert(self) -> Vec<OdbcColumnItem> {
-        match self {
-            AnySlice::Text(view) => {
-                let mut buffer = Vec::with_capacity(view.len());
-                for v in view.iter() {
-                    if let Some(x) = v {
-                        buffer.push(OdbcColumnItem {
-                            odbc_type: OdbcColumnType::Text,
-                            value: Some(BytesMut::from(x)),
-                        });
-                    } else {
-                        buffer.push(OdbcColumnItem {
-                            odbc_type: OdbcColumnType::Text,
-                            value: None,
-                        })
-                    }
-                }
-                buffer
-            }
-            AnySlice::WText(view) => {
-                let mut buffer = Vec::with_capacity(view.len());
-                for v in view.iter() {
-                    if let Some(x) = v {
-                        buffer.push(OdbcColumnItem {
-                            odbc_type: OdbcColumnType::WText,
-                            value: Some(BytesMut::from(x.to_string().unwrap().as_bytes())),
-                        });
-                    } else {
-                        buffer.push(OdbcColumnItem {
-                            odbc_type: OdbcColumnType::WText,
-                            value: None,
-                        })
-                    }
-                }
-                buffer
-            }
-            AnySlice::Binary(view) => {
-                let mut buffer = vec![];
-                for value in view.iter() {
-                    if let Some(bytes) = value {
-                        buffer.push(OdbcColumnItem {
-                            odbc_type: OdbcColumnType::Binary,
-                            value: Some(BytesMut::from(bytes)),
-                        })
-                    } else {
-                        buffer.push(OdbcColumnItem {
-                            odbc_type: OdbcColumnType::Binary,
-                            value: None,
-                        })
-                    }
-                }
-                buffer
+impl OdbcColumnItem {
+    pub fn is_null(&self) -> bool {
+        self.value.is_none()
+    }
+
+    /// The string of a `Text` or `WText` value, both hold UTF-8 so narrow and wide text columns
+    /// read the same. `None` for NULL, other types or a narrow value that isn't valid UTF-8.
+    pub fn as_string(&self) -> Option<&str> {
+        match self.odbc_type {
+            OdbcColumnType::Text | OdbcColumnType::WText => {
+                std::str::from_utf8(self.value.as_ref()?).ok()
             }
-            AnySlice::Date(view) => {
-                let mut buffer = vec![];
-                for value in view.iter() {
-                    let val = value.try_convert().unwrap();
-                    buffer.push(OdbcColumnItem {
-                        odbc_type: OdbcColumnType::Date,
-                        value: Some(BytesMut::from(val.to_string().as_bytes())),
-                    })
+            _ => None,
+        }
+    }
+
+    /// The plain text of the value, `None` for NULL.
+    /// `Binary` is rendered as upper hex with a `0x` prefix, `U8` as its decimal number.
+    pub fn value_to_string(&self) -> Option<String> {
+        let value = self.value.as_ref()?;
+        let s = match self.odbc_type {
+            OdbcColumnType::Binary => {
+                let mut s = String::with_capacity(2 + value.len() * 2);
+                s.push_str("0x");
+                for b in value.iter() {
+                    s.push_str(&format!("{:02X}", b));
                 }
-                buffer
+                s
             }
-            AnySlice::Timestamp(view) => {
-                let mut buffer = vec![];
-                for value in view.iter() {
-                    let val: time::PrimitiveDateTime = value.try_convert().unwrap();
-                    buffer.push(OdbcColumnItem {
-                        odbc_type: OdbcColumnType::Timestamp,
-                        value: Some(BytesMut::from(val.to_string().as_bytes())),
-                    })
-                }
-                buffer
+            OdbcColumnType::U8 => value.first().map(|b| b.to_string()).unwrap_or_default(),
+            _ => String::from_utf8_lossy(value).to_string(),
+        };
+        Some(s)
+    }
+
+    /// Typed comparison with `other`, `None` if the types can't be compared (e.g. text vs int).
+    ///
+    /// Integers and floats are widened to compare with each other, floats use total ordering so
+    /// NaN sorts after every number. `Text`/`WText` compare lexicographically, dates and times
+    /// chronologically. NULL is less than any value and equal to NULL of a comparable type.
+    pub fn cmp_value(&self, other: &OdbcColumnItem) -> Option<Ordering> {
+        let ordering = match (self.odbc_type, other.odbc_type) {
+            (
+                OdbcColumnType::Text | OdbcColumnType::WText,
+                OdbcColumnType::Text | OdbcColumnType::WText,
+            ) => Ordering::Equal,
+            (s, t) if s.is_numeric() && t.is_numeric() => Ordering::Equal,
+            (s, t) if s == t => Ordering::Equal,
+            _ => return None,
+        };
+        let (x, y) = match (&self.value, &other.value) {
+            (None, None) => return Some(ordering),
+            (None, Some(_)) => return Some(Ordering::Less),
+            (Some(_), None) => return Some(Ordering::Greater),
+            (Some(x), Some(y)) => (x, y),
+        };
+
+        let ordering = match self.odbc_type {
+            OdbcColumnType::Bit => (x.as_ref() == b"true").cmp(&(y.as_ref() == b"true")),
+            OdbcColumnType::Date => parse_to_date(x.clone())
+                .ok()?
+                .cmp(&parse_to_date(y.clone()).ok()?),
+            OdbcColumnType::Time => parse_to_time(x.clone())
+                .ok()?
+                .cmp(&parse_to_time(y.clone()).ok()?),
+            OdbcColumnType::Timestamp => parse_to_data_time(x.clone())
+                .ok()?
+                .cmp(&parse_to_data_time(y.clone()).ok()?),
+            OdbcColumnType::TimestampTz => self
+                .as_offset_date_time()?
+                .cmp(&other.as_offset_date_time()?),
+            t if t.is_numeric() => match (self.as_i64(), other.as_i64()) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                _ => self.as_f64()?.total_cmp(&other.as_f64()?),
+            },
+            _ => x.cmp(y),
+        };
+        Some(ordering)
+    }
+
+    /// The value of a `TimestampTz` as `OffsetDateTime`, `None` for NULL or other types.
+    pub fn as_offset_date_time(&self) -> Option<time::OffsetDateTime> {
+        match self.odbc_type {
+            OdbcColumnType::TimestampTz => {
+                parse_timestamp_tz(std::str::from_utf8(self.value.as_ref()?).ok()?).ok()
             }
-            AnySlice::Time(view) => {
-                let mut buffer = vec![];
-                for value in view.iter() {
-                    let val = value.try_convert().unwrap();
-                    buffer.push(OdbcColumnItem {
-                        odbc_type: OdbcColumnType::Time,
-                        value: Some(BytesMut::from(val.to_string().as_bytes())),
-                    })
-                }
-                buffer
+            _ => None,
+        }
+    }
+
+    /// Big-endian decode of an 8 bytes `Binary` value, e.g. a SQL Server `rowversion`.
+    /// `None` for NULL, other types or another length, the raw bytes stay in `value`.
+    pub fn as_rowversion(&self) -> Option<u64> {
+        match self.odbc_type {
+            OdbcColumnType::Binary => {
+                let bytes: [u8; 8] = self.value.as_ref()?.as_ref().try_into().ok()?;
+                Some(u64::from_be_bytes(bytes))
             }
-            AnySlice::I32(view) => {
-                let mut buffer = vec![];
-                for value in view.iter() {
-                    buffer.push(OdbcColumnItem {
-                        odbc_type: OdbcColumnType::I32,
-                        value: Some(BytesMut::from(value.to_string().as_bytes())),
-                    })
-                }
-                buffer
+            _ => None,
+        }
+    }
+
+    /// The value of an `Interval`, `None` for NULL or other types.
+    pub fn as_interval(&self) -> Option<Interval> {
+        match self.odbc_type {
+            OdbcColumnType::Interval => {
+                Interval::parse(std::str::from_utf8(self.value.as_ref()?).ok()?).ok()
             }
-            AnySlice::Bit(view) => {
-                let mut buffer = vec![];
-                for value in view.iter() {
-                    buffer.push(OdbcColumnItem {
-                        odbc_type: OdbcColumnType::Bit,
-                        value: Some(BytesMut::from(value.as_bool().to_string().as_bytes())),
-                    })
+            _ => None,
+        }
+    }
+
+    /// The value of an integer type as `i64`, `None` for NULL, other types or invalid text.
+    pub(crate) fn as_i64(&self) -> Option<i64> {
+        let value = self.value.as_ref()?;
+        match self.odbc_type {
+            OdbcColumnType::U8 => value.first().map(|b| *b as i64),
+            OdbcColumnType::I8
+            | OdbcColumnType::I16
+            | OdbcColumnType::I32
+            | OdbcColumnType::I64
+            | OdbcColumnType::U64 => std::str::from_utf8(value).ok()?.parse::<i64>().ok(),
+            _ => None,
+        }
+    }
+
+    /// 1 or 0 for a `Bit` value, `None` for NULL or other types, e.g. to sum boolean flags.
+    pub fn bit_as_int(&self) -> Option<i64> {
+        match self.odbc_type {
+            OdbcColumnType::Bit => Some((self.value.as_ref()?.as_ref() == b"true") as i64),
+            _ => None,
+        }
+    }
+
+    /// The value of a numeric type as `f64`, `None` for NULL, non-numeric types or invalid text.
+    pub fn as_f64(&self) -> Option<f64> {
+        let value = self.value.as_ref()?;
+        match self.odbc_type {
+            OdbcColumnType::U8 => value.first().map(|b| *b as f64),
+            t if t.is_numeric() => std::str::from_utf8(value).ok()?.parse::<f64>().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Min and max of a numeric column in one pass, `None` if it has no numeric value.
+///
+/// NULLs, non-numeric types and NaN are ignored. Values are compared as `f64`, so `I64` beyond 2^53
+/// lose precision, and decimals surfaced as `Text` are not considered.
+pub fn numeric_bounds(items: &[OdbcColumnItem]) -> Option<(f64, f64)> {
+    items
+        .iter()
+        .filter_map(|item| item.as_f64())
+        .filter(|v| !v.is_nan())
+        .fold(None, |bounds, v| match bounds {
+            None => Some((v, v)),
+            Some((min, max)) => Some((min.min(v), max.max(v))),
+        })
+}
+
+/// Number of true `Bit` cells, NULLs and other types aren't counted.
+pub fn count_true(items: &[OdbcColumnItem]) -> usize {
+    items
+        .iter()
+        .filter(|item| item.bit_as_int() == Some(1))
+        .count()
+}
+
+/// Up to `limit` distinct values of a column in first-seen order, e.g. for a filter dropdown.
+/// Values are equal when they have the same type and bytes, NULL is one value.
+pub fn distinct_values(items: &[OdbcColumnItem], limit: usize) -> Vec<OdbcColumnItem> {
+    let mut seen = HashSet::new();
+    items
+        .iter()
+        .filter(|item| seen.insert(*item))
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+/// Number of distinct values of a column like [`distinct_values`], counting stops at `limit`.
+pub fn distinct_count_bounded(items: &[OdbcColumnItem], limit: usize) -> usize {
+    let mut seen = HashSet::new();
+    for item in items {
+        if seen.len() >= limit {
+            break;
+        }
+        seen.insert(item);
+    }
+    seen.len()
+}
+
+/// Remove duplicate rows anywhere in `rows`, keeping the first occurrence and the original order.
+///
+/// Rows are equal when every cell has the same type and bytes, two NULLs are equal.
+pub fn dedup_rows(rows: Vec<Vec<OdbcColumnItem>>) -> Vec<Vec<OdbcColumnItem>> {
+    let mut keep = Vec::with_capacity(rows.len());
+    {
+        let mut seen = HashSet::with_capacity(rows.len());
+        for row in rows.iter() {
+            keep.push(seen.insert(row.as_slice()));
+        }
+    }
+    rows.into_iter()
+        .zip(keep)
+        .filter_map(|(row, keep)| keep.then_some(row))
+        .collect()
+}
+
+/// Remove consecutive duplicate rows only, for data already sorted. Cheaper than [`dedup_rows`].
+pub fn dedup_adjacent_rows(mut rows: Vec<Vec<OdbcColumnItem>>) -> Vec<Vec<OdbcColumnItem>> {
+    rows.dedup();
+    rows
+}
+
+/// Remove the columns whose values are NULL in every row, e.g. to trim the display of a sparse
+/// outer join. Rows are updated to stay in sync with `columns`. Nothing is removed without rows.
+pub fn drop_empty_columns(columns: &mut Vec<OdbcColumn>, data: &mut [Vec<OdbcColumnItem>]) {
+    if data.is_empty() {
+        return;
+    }
+    let keep = (0..columns.len())
+        .map(|index| {
+            data.iter()
+                .any(|row| row.get(index).is_some_and(|item| !item.is_null()))
+        })
+        .collect::<Vec<_>>();
+
+    let mut index = 0;
+    columns.retain(|_| {
+        index += 1;
+        keep[index - 1]
+    });
+    for row in data.iter_mut() {
+        let mut index = 0;
+        row.retain(|_| {
+            index += 1;
+            keep.get(index - 1).copied().unwrap_or(true)
+        });
+    }
+}
+
+/// Nullability observed in `rows`, one per column: `true` iff the column has at least one NULL.
+/// Views and some drivers report wrong nullability, this gives a tighter DDL than the metadata.
+/// Without rows every column is `false`.
+pub fn infer_nullability(columns: &[OdbcColumn], rows: &[Vec<OdbcColumnItem>]) -> Vec<bool> {
+    (0..columns.len())
+        .map(|index| {
+            rows.iter()
+                .any(|row| row.get(index).is_some_and(|item| item.is_null()))
+        })
+        .collect()
+}
+
+/// Split a wide result set into chunks of `cols_per_chunk` columns (at least 1), e.g. to print a
+/// 200-column table screen by screen. The `key_cols` indexes (out of range ones are ignored) are
+/// repeated first in every chunk so rows stay identifiable, and don't count in `cols_per_chunk`.
+pub fn chunk_columns(
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+    cols_per_chunk: usize,
+    key_cols: &[usize],
+) -> Vec<(Vec<OdbcColumn>, Vec<Vec<OdbcColumnItem>>)> {
+    let keys = key_cols
+        .iter()
+        .copied()
+        .filter(|index| *index < columns.len())
+        .collect::<Vec<_>>();
+    let others = (0..columns.len())
+        .filter(|index| !keys.contains(index))
+        .collect::<Vec<_>>();
+    if others.is_empty() {
+        return if keys.is_empty() {
+            vec![]
+        } else {
+            vec![select_columns(columns, data, &keys)]
+        };
+    }
+    others
+        .chunks(cols_per_chunk.max(1))
+        .map(|chunk| {
+            let indexes = keys.iter().chain(chunk).copied().collect::<Vec<_>>();
+            select_columns(columns, data, &indexes)
+        })
+        .collect()
+}
+
+fn select_columns(
+    columns: &[OdbcColumn],
+    data: &[Vec<OdbcColumnItem>],
+    indexes: &[usize],
+) -> (Vec<OdbcColumn>, Vec<Vec<OdbcColumnItem>>) {
+    let columns = indexes.iter().map(|i| columns[*i].clone()).collect();
+    let data = data
+        .iter()
+        .map(|row| {
+            indexes
+                .iter()
+                .filter_map(|i| row.get(*i).cloned())
+                .collect()
+        })
+        .collect();
+    (columns, data)
+}
+
+/// The single cell of a one row, one column result, e.g. `SELECT COUNT(*)`.
+/// Any other shape is an `OdbcHelperError::NotScalar`.
+pub fn scalar(
+    columns: &[OdbcColumn],
+    rows: Vec<Vec<OdbcColumnItem>>,
+) -> Result<OdbcColumnItem, OdbcHelperError> {
+    if columns.len() != 1 || rows.len() != 1 || rows[0].len() != 1 {
+        return Err(OdbcHelperError::NotScalar {
+            rows: rows.len(),
+            columns: columns.len(),
+        });
+    }
+    Ok(rows.into_iter().flatten().next().unwrap())
+}
+
+/// Concatenate result sets with compatible schemas, like `UNION ALL`, e.g. the result sets of a
+/// stored procedure.
+///
+/// Sets must have the same column count and each column the same family of types (numbers, text,
+/// binary, date, time or timestamp), lengths and precisions may differ. Column names come from
+/// the first set, a column is nullable if it is in any set.
+pub fn concat_result_sets(
+    sets: Vec<(Vec<OdbcColumn>, Vec<Vec<OdbcColumnItem>>)>,
+) -> Result<(Vec<OdbcColumn>, Vec<Vec<OdbcColumnItem>>), OdbcHelperError> {
+    let mut sets = sets.into_iter();
+    let (mut columns, mut data) = match sets.next() {
+        Some(set) => set,
+        None => return Ok((vec![], vec![])),
+    };
+    for (index, (other_columns, other_data)) in sets.enumerate() {
+        if other_columns.len() != columns.len() {
+            return Err(OdbcHelperError::SchemaMismatchError(format!(
+                "result set {} has {} columns, expected {}",
+                index + 1,
+                other_columns.len(),
+                columns.len()
+            )));
+        }
+        for (column, other) in columns.iter_mut().zip(other_columns.iter()) {
+            if type_family(&column.data_type) != type_family(&other.data_type) {
+                return Err(OdbcHelperError::SchemaMismatchError(format!(
+                    "result set {} column `{}` doesn't match `{}`",
+                    index + 1,
+                    other,
+                    column
+                )));
+            }
+            column.nullable |= other.nullable;
+        }
+        data.extend(other_data);
+    }
+    Ok((columns, data))
+}
+
+/// Check `row` against `columns` before binding it, e.g. for an `INSERT`.
+///
+/// The row must have one item per column, non-nullable columns must not be NULL and every item
+/// type must fit its column, see [`is_compatible`]. The error names the first offending column.
+pub fn validate_row(columns: &[OdbcColumn], row: &[OdbcColumnItem]) -> Result<(), OdbcHelperError> {
+    if row.len() != columns.len() {
+        return Err(OdbcHelperError::RowValidationError(format!(
+            "{} values for {} columns",
+            row.len(),
+            columns.len()
+        )));
+    }
+    for (column, item) in columns.iter().zip(row.iter()) {
+        if item.is_null() {
+            if !column.nullable {
+                return Err(OdbcHelperError::RowValidationError(format!(
+                    "NULL for `{}`",
+                    column
+                )));
+            }
+            continue;
+        }
+        if !is_compatible(item.odbc_type, &column.data_type) {
+            return Err(OdbcHelperError::RowValidationError(format!(
+                "{:?} value for `{}`",
+                item.odbc_type, column
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Whether a value of `odbc_type` can be bound to a column of `data_type`.
+///
+/// Types match their natural column type and widen without loss: smaller integers fit larger
+/// integer columns, integers and `F32` fit float columns, numbers and text fit `DECIMAL`/`NUMERIC`
+/// and a `Date` fits a `TIMESTAMP`. Text columns take `Text`, `WText`, `Json` and `Array`.
+/// Unknown and driver specific types accept any value.
+pub fn is_compatible(odbc_type: OdbcColumnType, data_type: &DataType) -> bool {
+    use OdbcColumnType as T;
+    // width of the smallest signed integer holding the range, 0..=255 needs 2 bytes
+    let integer_width = |t: T| match t {
+        T::I8 => Some(1),
+        T::U8 | T::I16 => Some(2),
+        T::I32 => Some(4),
+        T::I64 => Some(8),
+        _ => None,
+    };
+    match data_type {
+        DataType::TinyInt => matches!(odbc_type, T::I8 | T::U8 | T::I16),
+        DataType::SmallInt => integer_width(odbc_type).is_some_and(|w| w <= 2),
+        DataType::Integer => integer_width(odbc_type).is_some_and(|w| w <= 4),
+        DataType::BigInt => integer_width(odbc_type).is_some() || odbc_type == T::U64,
+        DataType::Bit => odbc_type == T::Bit,
+        DataType::Real => integer_width(odbc_type).is_some() || odbc_type == T::F32,
+        DataType::Float { .. } | DataType::Double => {
+            integer_width(odbc_type).is_some() || matches!(odbc_type, T::F32 | T::F64)
+        }
+        DataType::Decimal { .. } | DataType::Numeric { .. } => {
+            odbc_type.is_numeric() || matches!(odbc_type, T::Text | T::WText)
+        }
+        DataType::Char { .. }
+        | DataType::WChar { .. }
+        | DataType::Varchar { .. }
+        | DataType::WVarchar { .. }
+        | DataType::LongVarchar { .. } => {
+            matches!(odbc_type, T::Text | T::WText | T::Json | T::Array)
+        }
+        DataType::Binary { .. } | DataType::Varbinary { .. } | DataType::LongVarbinary { .. } => {
+            odbc_type == T::Binary
+        }
+        DataType::Date => odbc_type == T::Date,
+        DataType::Time { .. } => odbc_type == T::Time,
+        DataType::Timestamp { .. } => matches!(odbc_type, T::Timestamp | T::Date),
+        DataType::Unknown | DataType::Other { .. } => true,
+    }
+}
+
+#[derive(PartialEq, Eq)]
+enum TypeFamily {
+    Number,
+    Text,
+    Binary,
+    Date,
+    Time,
+    Timestamp,
+    Other,
+}
+
+fn type_family(data_type: &DataType) -> TypeFamily {
+    match data_type {
+        DataType::Numeric { .. }
+        | DataType::Decimal { .. }
+        | DataType::Integer
+        | DataType::SmallInt
+        | DataType::Float { .. }
+        | DataType::Real
+        | DataType::Double
+        | DataType::BigInt
+        | DataType::TinyInt
+        | DataType::Bit => TypeFamily::Number,
+        DataType::Char { .. }
+        | DataType::WChar { .. }
+        | DataType::Varchar { .. }
+        | DataType::WVarchar { .. }
+        | DataType::LongVarchar { .. } => TypeFamily::Text,
+        DataType::LongVarbinary { .. } | DataType::Varbinary { .. } | DataType::Binary { .. } => {
+            TypeFamily::Binary
+        }
+        DataType::Date => TypeFamily::Date,
+        DataType::Time { .. } => TypeFamily::Time,
+        DataType::Timestamp { .. } => TypeFamily::Timestamp,
+        DataType::Unknown | DataType::Other { .. } => TypeFamily::Other,
+    }
+}
+
+/// Row-level difference between two result sets, see [`diff_rows`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RowDiff {
+    /// indexes into `actual` of rows whose key is not in `expected`
+    pub added: Vec<usize>,
+    /// indexes into `expected` of rows whose key is not in `actual`
+    pub removed: Vec<usize>,
+    pub changed: Vec<RowChange>,
+}
+
+impl RowDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A row present in both result sets with different values.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RowChange {
+    pub expected_index: usize,
+    pub actual_index: usize,
+    /// column indexes of the cells that differ
+    pub columns: Vec<usize>,
+}
+
+/// Match rows of `expected` and `actual` by the `key_cols` cells and report the differences.
+///
+/// Cells are compared by value equality, two NULLs are equal. Rows sharing a key are matched in
+/// order. An empty `key_cols` matches rows by position.
+pub fn diff_rows<'a>(
+    expected: &'a [Vec<OdbcColumnItem>],
+    actual: &'a [Vec<OdbcColumnItem>],
+    key_cols: &[usize],
+) -> RowDiff {
+    // the position is only part of the key when matching by position
+    let key = |index: usize, row: &'a [OdbcColumnItem]| {
+        let position = if key_cols.is_empty() { index } else { 0 };
+        let cells = key_cols.iter().map(|c| row.get(*c)).collect::<Vec<_>>();
+        (position, cells)
+    };
+
+    let mut actual_by_key = HashMap::<_, VecDeque<usize>>::new();
+    for (index, row) in actual.iter().enumerate() {
+        actual_by_key
+            .entry(key(index, row))
+            .or_default()
+            .push_back(index);
+    }
+
+    let mut diff = RowDiff::default();
+    let mut matched = vec![false; actual.len()];
+    for (expected_index, row) in expected.iter().enumerate() {
+        let actual_index = actual_by_key
+            .get_mut(&key(expected_index, row))
+            .and_then(|indexes| indexes.pop_front());
+        let actual_index = match actual_index {
+            Some(index) => index,
+            None => {
+                diff.removed.push(expected_index);
+                continue;
+            }
+        };
+        matched[actual_index] = true;
+
+        let actual_row = &actual[actual_index];
+        let columns = (0..row.len().max(actual_row.len()))
+            .filter(|c| row.get(*c) != actual_row.get(*c))
+            .collect::<Vec<_>>();
+        if !columns.is_empty() {
+            diff.changed.push(RowChange {
+                expected_index,
+                actual_index,
+                columns,
+            });
+        }
+    }
+    diff.added = (0..actual.len()).filter(|i| !matched[*i]).collect();
+    diff
+}
+
+/// The `(column name, old, new)` cells that differ between two versions of a row, e.g. for audit
+/// logging the rows of a [`RowChange`].
+///
+/// Cells are compared as in [`diff_rows`], cells past the end of the shorter row or of `columns`
+/// are ignored.
+pub fn changed_cells(
+    old: &[OdbcColumnItem],
+    new: &[OdbcColumnItem],
+    columns: &[OdbcColumn],
+) -> Vec<(String, OdbcColumnItem, OdbcColumnItem)> {
+    old.iter()
+        .zip(new.iter())
+        .zip(columns.iter())
+        .filter(|((old, new), _)| old != new)
+        .map(|((old, new), column)| (column.name.clone(), old.clone(), new.clone()))
+        .collect()
+}
+
+/// Where NULLs go when sorting, regardless of the direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NullOrder {
+    First,
+    /// default, like Postgres for ascending order.
+    #[default]
+    Last,
+}
+
+/// Stable sort of `rows` by the column `col`, NULLs last. See [`sort_rows_by_with_nulls`].
+pub fn sort_rows_by(rows: &mut [Vec<OdbcColumnItem>], col: usize, ascending: bool) {
+    sort_rows_by_with_nulls(rows, col, ascending, NullOrder::default())
+}
+
+/// Stable sort of `rows` by the column `col`, ties keep their original order.
+///
+/// Numbers compare by value, text lexicographically and dates/times chronologically.
+/// Values that can't be compared (different types or unparsable) are treated as ties.
+pub fn sort_rows_by_with_nulls(
+    rows: &mut [Vec<OdbcColumnItem>],
+    col: usize,
+    ascending: bool,
+    nulls: NullOrder,
+) {
+    let null_first = match nulls {
+        NullOrder::First => Ordering::Less,
+        NullOrder::Last => Ordering::Greater,
+    };
+    rows.sort_by(|a, b| {
+        let a = a.get(col).filter(|i| !i.is_null());
+        let b = b.get(col).filter(|i| !i.is_null());
+        match (a, b) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => null_first,
+            (Some(_), None) => null_first.reverse(),
+            (Some(a), Some(b)) => {
+                let ordering = a.cmp_value(b).unwrap_or(Ordering::Equal);
+                if ascending {
+                    ordering
+                } else {
+                    ordering.reverse()
                 }
-                buffer
             }
+        }
+    });
+}
 
-            AnySlice::F64(view) => {
-                let mut buffer = vec![];
-                for value in view.iter() {
-                    buffer.push(OdbcColumnItem {
-                        odbc_type: OdbcColumnType::F64,
-                        value: Some(BytesMut::from(value.to_string().as_bytes())),
+/// Bytes of each raw value logged by [`trace_raw_values`].
+#[cfg(feature = "raw-trace")]
+const RAW_TRACE_BYTES: usize = 32;
+
+/// Log the raw values the driver delivered for a text/binary column at `trace` level: the row
+/// in the batch, the length and the first bytes in hex, so a surprising converted value can be
+/// correlated with its buffer. `WText` values are logged as UTF-16LE bytes.
+/// Only compiled with the `raw-trace` feature.
+#[cfg(feature = "raw-trace")]
+pub fn trace_raw_values(column: &OdbcColumn, view: AnySlice) {
+    if !log_enabled!(log::Level::Trace) {
+        return;
+    }
+    let trace = |row: usize, value: Option<Vec<u8>>| match value {
+        Some(bytes) => {
+            let hex = bytes
+                .iter()
+                .take(RAW_TRACE_BYTES)
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>();
+            let ellipsis = if bytes.len() > RAW_TRACE_BYTES {
+                ".."
+            } else {
+                ""
+            };
+            trace!(
+                "column `{}` row {}: {} bytes 0x{}{}",
+                column.name,
+                row,
+                bytes.len(),
+                hex,
+                ellipsis
+            );
+        }
+        None => trace!("column `{}` row {}: NULL", column.name, row),
+    };
+    match view {
+        AnySlice::Text(view) => {
+            for (row, v) in view.iter().enumerate() {
+                trace(row, v.map(|x| x.to_vec()));
+            }
+        }
+        AnySlice::WText(view) => {
+            for (row, v) in view.iter().enumerate() {
+                trace(
+                    row,
+                    v.map(|x| x.as_slice().iter().flat_map(|c| c.to_le_bytes()).collect()),
+                );
+            }
+        }
+        AnySlice::Binary(view) => {
+            for (row, v) in view.iter().enumerate() {
+                trace(row, v.map(|x| x.to_vec()));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Estimate the in-memory footprint of row data in bytes.
+///
+/// Sums the stack size of every row `Vec` and `OdbcColumnItem` plus the heap length of each value.
+/// Allocator overhead and spare capacity are not counted, so treat the result as a lower bound.
+pub fn estimated_size_bytes(rows: &[Vec<OdbcColumnItem>]) -> usize {
+    rows.iter()
+        .map(|row| {
+            mem::size_of::<Vec<OdbcColumnItem>>()
+                + row
+                    .iter()
+                    .map(|item| {
+                        mem::size_of::<OdbcColumnItem>()
+                            + item.value.as_ref().map(|v| v.len()).unwrap_or_default()
                     })
-                }
-                buffer
+                    .sum::<usize>()
+        })
+        .sum()
+}
+
+impl fmt::Display for OdbcColumnItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Collapses the cell types into the backend-neutral [`SqlValue`]:
+/// - `I8`..`I64` and `U8` become `Int`, `F32`/`F64` become `Float`, `Bit` becomes `Bool`
+/// - `Text`, `WText`, `Array` and `Interval` become `Text`, `TimestampTz` becomes `Timestamp`
+/// - NULL of any type becomes `Null`
+///
+/// Numbers that don't parse are kept as `Text`.
+impl From<OdbcColumnItem> for SqlValue {
+    fn from(item: OdbcColumnItem) -> Self {
+        let value = match &item.value {
+            Some(value) => value,
+            None => return SqlValue::Null,
+        };
+        let text = || String::from_utf8_lossy(value).to_string();
+        match item.odbc_type {
+            OdbcColumnType::Bit => SqlValue::Bool(value.as_ref() == b"true"),
+            OdbcColumnType::Binary => SqlValue::Bytes(value.to_vec()),
+            OdbcColumnType::Date => SqlValue::Date(text()),
+            OdbcColumnType::Time => SqlValue::Time(text()),
+            OdbcColumnType::Timestamp | OdbcColumnType::TimestampTz => SqlValue::Timestamp(text()),
+            OdbcColumnType::Json => SqlValue::Json(text()),
+            OdbcColumnType::F32 | OdbcColumnType::F64 => match item.as_f64() {
+                Some(v) => SqlValue::Float(v),
+                None => SqlValue::Text(text()),
+            },
+            t if t.is_numeric() => match item.as_i64() {
+                Some(v) => SqlValue::Int(v),
+                None => SqlValue::Text(text()),
+            },
+            _ => SqlValue::Text(text()),
+        }
+    }
+}
+
+/// The widest cell type for each value: `Int` becomes `I64`, `Float` becomes `F64` and
+/// `Timestamp` becomes `TimestampTz` when it has an offset. `Null` becomes a NULL `Text`.
+///
+/// Date, time and timestamp text that doesn't parse is an `OdbcHelperError::TypeConversionError`.
+impl TryFrom<SqlValue> for OdbcColumnItem {
+    type Error = OdbcHelperError;
+
+    fn try_from(value: SqlValue) -> Result<Self, Self::Error> {
+        let invalid = |kind: &str, text: &str| {
+            OdbcHelperError::TypeConversionError(format!("invalid {} `{}`", kind, text))
+        };
+        let (odbc_type, bytes) = match value {
+            SqlValue::Null => {
+                return Ok(OdbcColumnItem {
+                    odbc_type: OdbcColumnType::Text,
+                    value: None,
+                })
+            }
+            SqlValue::Bool(v) => (
+                OdbcColumnType::Bit,
+                BytesMut::from(if v { "true" } else { "false" }),
+            ),
+            SqlValue::Int(v) => (OdbcColumnType::I64, BytesMut::from(v.to_string().as_str())),
+            SqlValue::Float(v) => (OdbcColumnType::F64, BytesMut::from(v.to_string().as_str())),
+            SqlValue::Text(v) => (OdbcColumnType::Text, BytesMut::from(v.as_str())),
+            SqlValue::Json(v) => (OdbcColumnType::Json, BytesMut::from(v.as_str())),
+            SqlValue::Bytes(v) => (OdbcColumnType::Binary, BytesMut::from(v.as_slice())),
+            SqlValue::Date(v) => {
+                parse_to_date(BytesMut::from(v.as_str())).map_err(|_| invalid("date", &v))?;
+                (OdbcColumnType::Date, BytesMut::from(v.as_str()))
+            }
+            SqlValue::Time(v) => {
+                parse_to_time(BytesMut::from(v.as_str())).map_err(|_| invalid("time", &v))?;
+                (OdbcColumnType::Time, BytesMut::from(v.as_str()))
             }
-            AnySlice::F32(view) => {
-                let mut buffer = vec![];
-                for value in view.iter() {
+            SqlValue::Timestamp(v) => {
+                let odbc_type = if parse_timestamp_tz(&v).is_ok() {
+                    OdbcColumnType::TimestampTz
+                } else {
+                    parse_to_data_time(BytesMut::from(v.as_str()))
+                        .map_err(|_| invalid("timestamp", &v))?;
+                    OdbcColumnType::Timestamp
+                };
+                (odbc_type, BytesMut::from(v.as_str()))
+            }
+        };
+        Ok(OdbcColumnItem {
+            odbc_type,
+            value: Some(bytes),
+        })
+    }
+}
+
+/// Convert a column buffer of a fetched batch into items.
+///
+/// # Panics
+///
+/// On cells that have no item representation, e.g. a `DATE` out of the supported range or
+/// invalid UTF-16 text, use [`try_convert_view`] to get an error instead.
+impl Convert<Vec<OdbcColumnItem>> for AnySlice<'_> {
+    fn convert(self) -> Vec<OdbcColumnItem> {
+        convert_slice(self, None).unwrap()
+    }
+}
+
+/// Convert a column buffer of a fetched batch into items like `Convert` does, but cells that
+/// can't be represented are an `OdbcHelperError::ColumnConversionError` naming `column`
+/// instead of a panic. Timestamps are truncated to the column's [`fraction_digits`], so a
+/// `TIMESTAMP(3)` value has no spurious digits below the millisecond.
+pub fn try_convert_view(
+    view: AnySlice,
+    column: &OdbcColumn,
+) -> Result<Vec<OdbcColumnItem>, OdbcHelperError> {
+    convert_slice(view, fraction_digits(&column.data_type)).map_err(|e| {
+        OdbcHelperError::ColumnConversionError {
+            column: column.name.clone(),
+            source: Box::new(e),
+        }
+    })
+}
+
+/// `digits`: fractional second digits timestamps are truncated to, `None` keeps nanoseconds.
+fn convert_slice(
+    view: AnySlice,
+    digits: Option<i16>,
+) -> Result<Vec<OdbcColumnItem>, OdbcHelperError> {
+    Ok(match view {
+        AnySlice::Text(view) => {
+            let mut buffer = Vec::with_capacity(view.len());
+            for v in view.iter() {
+                if let Some(x) = v {
                     buffer.push(OdbcColumnItem {
-                        odbc_type: OdbcColumnType::F32,
-                        value: Some(BytesMut::from(value.to_string().as_bytes())),
+                        odbc_type: OdbcColumnType::Text,
+                        value: Some(BytesMut::from(x)),
+                    });
+                } else {
+                    buffer.push(OdbcColumnItem {
+                        odbc_type: OdbcColumnType::Text,
+                        value: None,
                     })
                 }
-                buffer
             }
-            AnySlice::I8(view) => {
-                let mut buffer = vec![];
-                for value in view.iter() {
+            buffer
+        }
+        AnySlice::WText(view) => {
+            let mut buffer = Vec::with_capacity(view.len());
+            for v in view.iter() {
+                // A zero-length value is `Some("")`, only the NULL indicator makes it `None`
+                if let Some(x) = v {
                     buffer.push(OdbcColumnItem {
-                        odbc_type: OdbcColumnType::I8,
-                        value: Some(BytesMut::from(value.to_string().as_bytes())),
+                        odbc_type: OdbcColumnType::WText,
+                        value: Some(BytesMut::from(
+                            x.to_string()
+                                .map_err(|e| {
+                                    OdbcHelperError::TypeConversionError(format!("WText:{}", e))
+                                })?
+                                .as_bytes(),
+                        )),
+                    });
+                } else {
+                    buffer.push(OdbcColumnItem {
+                        odbc_type: OdbcColumnType::WText,
+                        value: None,
                     })
                 }
-                buffer
             }
-            AnySlice::I16(view) => {
-                let mut buffer = vec![];
-                for value in view.iter() {
+            buffer
+        }
+        AnySlice::Binary(view) => {
+            let mut buffer = vec![];
+            for value in view.iter() {
+                if let Some(bytes) = value {
                     buffer.push(OdbcColumnItem {
-                        odbc_type: OdbcColumnType::I16,
-                        value: Some(BytesMut::from(value.to_string().as_bytes())),
+                        odbc_type: OdbcColumnType::Binary,
+                        value: Some(BytesMut::from(bytes)),
                     })
-                }
-                buffer
-            }
-            AnySlice::I64(view) => {
-                let mut buffer = vec![];
-                for value in view.iter() {
+                } else {
                     buffer.push(OdbcColumnItem {
-                        odbc_type: OdbcColumnType::I64,
-                        value: Some(BytesMut::from(value.to_string().as_bytes())),
+                        odbc_type: OdbcColumnType::Binary,
+                        value: None,
                     })
                 }
-                buffer
             }
-            AnySlice::U8(view) => {
-                let mut buffer = vec![];
+            buffer
+        }
+        AnySlice::Date(view) => {
+            let mut buffer = vec![];
+            for value in view.iter() {
+                let val = value.try_convert()?;
+                buffer.push(OdbcColumnItem {
+                    odbc_type: OdbcColumnType::Date,
+                    value: Some(BytesMut::from(val.to_string().as_bytes())),
+                })
+            }
+            buffer
+        }
+        AnySlice::Timestamp(view) => {
+            let mut buffer = vec![];
+            for value in view.iter() {
+                let val: time::PrimitiveDateTime =
+                    truncate_fraction(*value, digits).try_convert()?;
+                buffer.push(OdbcColumnItem {
+                    odbc_type: OdbcColumnType::Timestamp,
+                    value: Some(BytesMut::from(val.to_string().as_bytes())),
+                })
+            }
+            buffer
+        }
+        AnySlice::Time(view) => view.iter().map(time_item).collect::<Result<_, _>>()?,
+        AnySlice::I32(view) => {
+            let mut buffer = vec![];
+            for value in view.iter() {
+                buffer.push(OdbcColumnItem {
+                    odbc_type: OdbcColumnType::I32,
+                    value: Some(BytesMut::from(value.to_string().as_bytes())),
+                })
+            }
+            buffer
+        }
+        AnySlice::Bit(view) => {
+            let mut buffer = vec![];
+            for value in view.iter() {
+                buffer.push(OdbcColumnItem {
+                    odbc_type: OdbcColumnType::Bit,
+                    value: Some(BytesMut::from(value.as_bool().to_string().as_bytes())),
+                })
+            }
+            buffer
+        }
+
+        AnySlice::F64(view) => {
+            let mut buffer = vec![];
+            for value in view.iter() {
+                buffer.push(OdbcColumnItem {
+                    odbc_type: OdbcColumnType::F64,
+                    value: Some(BytesMut::from(value.to_string().as_bytes())),
+                })
+            }
+            buffer
+        }
+        AnySlice::F32(view) => {
+            let mut buffer = vec![];
+            for value in view.iter() {
+                buffer.push(OdbcColumnItem {
+                    odbc_type: OdbcColumnType::F32,
+                    value: Some(BytesMut::from(value.to_string().as_bytes())),
+                })
+            }
+            buffer
+        }
+        AnySlice::I8(view) => {
+            let mut buffer = vec![];
+            for value in view.iter() {
+                buffer.push(OdbcColumnItem {
+                    odbc_type: OdbcColumnType::I8,
+                    value: Some(BytesMut::from(value.to_string().as_bytes())),
+                })
+            }
+            buffer
+        }
+        AnySlice::I16(view) => {
+            let mut buffer = vec![];
+            for value in view.iter() {
+                buffer.push(OdbcColumnItem {
+                    odbc_type: OdbcColumnType::I16,
+                    value: Some(BytesMut::from(value.to_string().as_bytes())),
+                })
+            }
+            buffer
+        }
+        AnySlice::I64(view) => {
+            let mut buffer = vec![];
+            for value in view.iter() {
+                buffer.push(OdbcColumnItem {
+                    odbc_type: OdbcColumnType::I64,
+                    value: Some(BytesMut::from(value.to_string().as_bytes())),
+                })
+            }
+            buffer
+        }
+        AnySlice::U8(view) => {
+            let mut buffer = vec![];
 
-                for value in view.iter() {
-                    buffer.push(OdbcColumnItem {
-                        odbc_type: OdbcColumnType::U8,
-                        value: Some(BytesMut::from(vec![*value].as_slice())),
-                    })
-                }
-                buffer
+            for value in view.iter() {
+                buffer.push(OdbcColumnItem {
+                    odbc_type: OdbcColumnType::U8,
+                    value: Some(BytesMut::from(vec![*value].as_slice())),
+                })
             }
-            AnySlice::NullableDate(view) => {
-                let (values, indicators) = view.raw_values();
-                let values = values.to_vec();
+            buffer
+        }
+        AnySlice::NullableDate(view) => {
+            let (values, indicators) = view.raw_values();
+            let values = values.to_vec();
 
-                values
-                    .iter()
-                    .enumerate()
-                    .map(|(index, value)| {
-                        if indicators[index] != NULL_DATA {
-                            let val = value.try_convert().unwrap();
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::Date,
-                                value: Some(BytesMut::from(val.to_string().as_bytes())),
-                            }
-                        } else {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::Date,
-                                value: None,
-                            }
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    Ok(if indicators[index] != NULL_DATA {
+                        let val = value.try_convert()?;
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::Date,
+                            value: Some(BytesMut::from(val.to_string().as_bytes())),
+                        }
+                    } else {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::Date,
+                            value: None,
                         }
                     })
-                    .collect()
-            }
-            AnySlice::NullableTime(view) => {
-                let (values, indicators) = view.raw_values();
-                let values = values.to_vec();
+                })
+                .collect::<Result<_, OdbcHelperError>>()?
+        }
+        AnySlice::NullableTime(view) => {
+            let (values, indicators) = view.raw_values();
+            let values = values.to_vec();
 
-                values
-                    .iter()
-                    .enumerate()
-                    .map(|(index, value)| {
-                        if indicators[index] != NULL_DATA {
-                            let val = value.try_convert().unwrap();
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::Time,
-                                value: Some(BytesMut::from(val.to_string().as_bytes())),
-                            }
-                        } else {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::Time,
-                                value: None,
-                            }
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    Ok(if indicators[index] != NULL_DATA {
+                        time_item(value)?
+                    } else {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::Time,
+                            value: None,
                         }
                     })
-                    .collect()
-            }
-            AnySlice::NullableTimestamp(view) => {
-                let (values, indicators) = view.raw_values();
-                let values = values.to_vec();
+                })
+                .collect::<Result<_, OdbcHelperError>>()?
+        }
+        AnySlice::NullableTimestamp(view) => {
+            let (values, indicators) = view.raw_values();
+            let values = values.to_vec();
 
-                values
-                    .iter()
-                    .enumerate()
-                    .map(|(index, value)| {
-                        if indicators[index] != NULL_DATA {
-                            let val: time::PrimitiveDateTime = value.try_convert().unwrap();
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::Timestamp,
-                                value: Some(BytesMut::from(val.to_string().as_bytes())),
-                            }
-                        } else {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::Timestamp,
-                                value: None,
-                            }
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    Ok(if indicators[index] != NULL_DATA {
+                        let val: time::PrimitiveDateTime =
+                            truncate_fraction(*value, digits).try_convert()?;
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::Timestamp,
+                            value: Some(BytesMut::from(val.to_string().as_bytes())),
+                        }
+                    } else {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::Timestamp,
+                            value: None,
                         }
                     })
-                    .collect()
-            }
-            AnySlice::NullableF64(view) => {
-                let (values, indicators) = view.raw_values();
-                let values = values.to_vec();
+                })
+                .collect::<Result<_, OdbcHelperError>>()?
+        }
+        AnySlice::NullableF64(view) => {
+            let (values, indicators) = view.raw_values();
+            let values = values.to_vec();
 
-                values
-                    .iter()
-                    .enumerate()
-                    .map(|(index, value)| {
-                        if indicators[index] != NULL_DATA {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::F64,
-                                value: Some(BytesMut::from(value.to_string().as_bytes())),
-                            }
-                        } else {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::F64,
-                                value: None,
-                            }
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    if indicators[index] != NULL_DATA {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::F64,
+                            value: Some(BytesMut::from(value.to_string().as_bytes())),
                         }
-                    })
-                    .collect()
-            }
-            AnySlice::NullableF32(view) => {
-                let (values, indicators) = view.raw_values();
-                let values = values.to_vec();
+                    } else {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::F64,
+                            value: None,
+                        }
+                    }
+                })
+                .collect()
+        }
+        AnySlice::NullableF32(view) => {
+            let (values, indicators) = view.raw_values();
+            let values = values.to_vec();
 
-                values
-                    .iter()
-                    .enumerate()
-                    .map(|(index, value)| {
-                        if indicators[index] != NULL_DATA {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::F32,
-                                value: Some(BytesMut::from(value.to_string().as_bytes())),
-                            }
-                        } else {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::F32,
-                                value: None,
-                            }
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    if indicators[index] != NULL_DATA {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::F32,
+                            value: Some(BytesMut::from(value.to_string().as_bytes())),
                         }
-                    })
-                    .collect()
-            }
-            AnySlice::NullableI8(view) => {
-                let (values, indicators) = view.raw_values();
-                let values = values.to_vec();
+                    } else {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::F32,
+                            value: None,
+                        }
+                    }
+                })
+                .collect()
+        }
+        AnySlice::NullableI8(view) => {
+            let (values, indicators) = view.raw_values();
+            let values = values.to_vec();
 
-                values
-                    .iter()
-                    .enumerate()
-                    .map(|(index, value)| {
-                        if indicators[index] != NULL_DATA {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::I8,
-                                value: Some(BytesMut::from(value.to_string().as_bytes())),
-                            }
-                        } else {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::I8,
-                                value: None,
-                            }
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    if indicators[index] != NULL_DATA {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::I8,
+                            value: Some(BytesMut::from(value.to_string().as_bytes())),
                         }
-                    })
-                    .collect()
-            }
-            AnySlice::NullableI16(view) => {
-                let (values, indicators) = view.raw_values();
-                let values = values.to_vec();
+                    } else {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::I8,
+                            value: None,
+                        }
+                    }
+                })
+                .collect()
+        }
+        AnySlice::NullableI16(view) => {
+            let (values, indicators) = view.raw_values();
+            let values = values.to_vec();
 
-                values
-                    .iter()
-                    .enumerate()
-                    .map(|(index, value)| {
-                        if indicators[index] != NULL_DATA {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::I16,
-                                value: Some(BytesMut::from(value.to_string().as_bytes())),
-                            }
-                        } else {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::I16,
-                                value: None,
-                            }
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    if indicators[index] != NULL_DATA {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::I16,
+                            value: Some(BytesMut::from(value.to_string().as_bytes())),
                         }
-                    })
-                    .collect()
-            }
-            AnySlice::NullableI32(view) => {
-                let (values, indicators) = view.raw_values();
-                let values = values.to_vec();
+                    } else {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::I16,
+                            value: None,
+                        }
+                    }
+                })
+                .collect()
+        }
+        AnySlice::NullableI32(view) => {
+            let (values, indicators) = view.raw_values();
+            let values = values.to_vec();
 
-                values
-                    .iter()
-                    .enumerate()
-                    .map(|(index, value)| {
-                        if indicators[index] != NULL_DATA {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::I32,
-                                value: Some(BytesMut::from(value.to_string().as_bytes())),
-                            }
-                        } else {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::I32,
-                                value: None,
-                            }
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    if indicators[index] != NULL_DATA {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::I32,
+                            value: Some(BytesMut::from(value.to_string().as_bytes())),
                         }
-                    })
-                    .collect()
-            }
-            AnySlice::NullableI64(view) => {
-                let (values, indicators) = view.raw_values();
-                let values = values.to_vec();
+                    } else {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::I32,
+                            value: None,
+                        }
+                    }
+                })
+                .collect()
+        }
+        AnySlice::NullableI64(view) => {
+            let (values, indicators) = view.raw_values();
+            let values = values.to_vec();
 
-                values
-                    .iter()
-                    .enumerate()
-                    .map(|(index, value)| {
-                        if indicators[index] != NULL_DATA {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::I64,
-                                value: Some(BytesMut::from(value.to_string().as_bytes())),
-                            }
-                        } else {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::I64,
-                                value: None,
-                            }
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    if indicators[index] != NULL_DATA {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::I64,
+                            value: Some(BytesMut::from(value.to_string().as_bytes())),
                         }
-                    })
-                    .collect()
-            }
-            AnySlice::NullableU8(view) => {
-                let (values, indicators) = view.raw_values();
-                let values = values.to_vec();
+                    } else {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::I64,
+                            value: None,
+                        }
+                    }
+                })
+                .collect()
+        }
+        AnySlice::NullableU8(view) => {
+            let (values, indicators) = view.raw_values();
+            let values = values.to_vec();
 
-                values
-                    .iter()
-                    .enumerate()
-                    .map(|(index, value)| {
-                        if indicators[index] != NULL_DATA {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::U8,
-                                value: Some(BytesMut::from(vec![*value].as_slice())),
-                            }
-                        } else {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::U8,
-                                value: None,
-                            }
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    if indicators[index] != NULL_DATA {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::U8,
+                            value: Some(BytesMut::from(vec![*value].as_slice())),
                         }
-                    })
-                    .collect()
-            }
-            AnySlice::NullableBit(view) => {
-                let (values, indicators) = view.raw_values();
-                let values = values.to_vec();
+                    } else {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::U8,
+                            value: None,
+                        }
+                    }
+                })
+                .collect()
+        }
+        AnySlice::NullableBit(view) => {
+            let (values, indicators) = view.raw_values();
+            let values = values.to_vec();
 
-                values
-                    .iter()
-                    .enumerate()
-                    .map(|(index, value)| {
-                        if indicators[index] != NULL_DATA {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::Bit,
-                                value: Some(BytesMut::from(value.as_bool().to_string().as_bytes())),
-                            }
-                        } else {
-                            OdbcColumnItem {
-                                odbc_type: OdbcColumnType::Bit,
-                                value: None,
-                            }
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    if indicators[index] != NULL_DATA {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::Bit,
+                            value: Some(BytesMut::from(value.as_bool().to_string().as_bytes())),
                         }
-                    })
-                    .collect()
-            }
+                    } else {
+                        OdbcColumnItem {
+                            odbc_type: OdbcColumnType::Bit,
+                            value: None,
+                        }
+                    }
+                })
+                .collect()
         }
+    })
+}
+
+/// A non-NULL `TIME` cell. Elapsed-time columns may hold values past `24:00:00`, e.g.
+/// `99:00:00`, which `time::Time` can't represent, those become an `Interval` instead.
+fn time_item(value: &Time) -> Result<OdbcColumnItem, OdbcHelperError> {
+    if value.hour >= 24 {
+        let seconds = value.hour as i64 * 3600 + value.minute as i64 * 60 + value.second as i64;
+        let interval = Interval {
+            nanos: seconds * 1_000_000_000,
+            ..Default::default()
+        };
+        return Ok(OdbcColumnItem {
+            odbc_type: OdbcColumnType::Interval,
+            value: Some(BytesMut::from(interval.to_string().as_bytes())),
+        });
     }
+    let val: time::Time = value
+        .try_convert()
+        .map_err(|e: time::Error| OdbcHelperError::TypeConversionError(format!("Time:{}", e)))?;
+    Ok(OdbcColumnItem {
+        odbc_type: OdbcColumnType::Time,
+        value: Some(BytesMut::from(val.to_string().as_bytes())),
+    })
 }
 
 /// Convert `odbc_api::sys::Date` to `time::Date`
 ///
+/// Years use astronomical numbering as `time` does: year `0` is 1 BC, `-1` is 2 BC, and range
+/// from -9999 to 9999. Anything else, e.g. the MySQL zero date `0000-00-00` or `2022-02-30`, is
+/// an `OdbcHelperError::InvalidDate`.
+///
 /// # Example
 ///
 /// ```rust
@@ -527,14 +2205,20 @@ impl Convert<Vec<OdbcColumnItem>> for AnySlice<'_> {
 ///
 /// ```
 impl TryConvert<time::Date> for Date {
-    type Error = time::Error;
+    type Error = OdbcHelperError;
 
     fn try_convert(self) -> Result<time::Date, Self::Error> {
-        Ok(time::Date::from_calendar_date(
-            self.year as i32,
-            time::Month::try_from(self.month as u8)?,
-            self.day as u8,
-        )?)
+        let invalid = || OdbcHelperError::InvalidDate {
+            year: self.year,
+            month: self.month,
+            day: self.day,
+        };
+        let month = u8::try_from(self.month)
+            .ok()
+            .and_then(|m| time::Month::try_from(m).ok())
+            .ok_or_else(invalid)?;
+        let day = u8::try_from(self.day).map_err(|_| invalid())?;
+        time::Date::from_calendar_date(self.year as i32, month, day).map_err(|_| invalid())
     }
 }
 
@@ -580,10 +2264,61 @@ impl TryConvert<time::Time> for (Time, u32) {
     }
 }
 
-impl TryConvert<(time::Date, time::Time)> for Timestamp {
+/// Like `(Time, u32)` with the nanoseconds truncated to `digits` fractional digits, the column's
+/// [`fraction_digits`], so a `TIME(3)` value has no spurious digits below the millisecond.
+impl TryConvert<time::Time> for (Time, u32, i16) {
     type Error = time::Error;
+    fn try_convert(self) -> Result<time::Time, Self::Error> {
+        let (time, nanosecond, digits) = self;
+        (time, truncate_nanos(nanosecond, digits)).try_convert()
+    }
+}
+
+fn truncate_nanos(nanosecond: u32, digits: i16) -> u32 {
+    let unit = 10u32.pow(9 - digits.clamp(0, 9) as u32);
+    nanosecond / unit * unit
+}
+
+/// `value` with its fraction truncated to `digits`, unchanged for `None`.
+fn truncate_fraction(value: Timestamp, digits: Option<i16>) -> Timestamp {
+    match digits {
+        Some(digits) => Timestamp {
+            fraction: truncate_nanos(value.fraction, digits),
+            ..value
+        },
+        None => value,
+    }
+}
+
+/// Every field is checked first, an out of range one (e.g. month 13, day 31 in April, hour 24
+/// or a fraction of a second or more) is an `OdbcHelperError::InvalidTimestamp` naming it.
+impl TryConvert<(time::Date, time::Time)> for Timestamp {
+    type Error = OdbcHelperError;
 
     fn try_convert(self) -> Result<(time::Date, time::Time), Self::Error> {
+        let check = |field, value: i64, range: std::ops::RangeInclusive<i64>| {
+            if range.contains(&value) {
+                Ok(())
+            } else {
+                Err(OdbcHelperError::InvalidTimestamp { field, value })
+            }
+        };
+        check("year", self.year as i64, -9999..=9999)?;
+        check("month", self.month as i64, 1..=12)?;
+        check("day", self.day as i64, 1..=31)?;
+        let month = time::Month::try_from(self.month as u8).unwrap();
+        if time::Date::from_calendar_date(self.year as i32, month, self.day as u8).is_err() {
+            // past the end of the month, e.g. February 30th
+            return Err(OdbcHelperError::InvalidTimestamp {
+                field: "day",
+                value: self.day as i64,
+            });
+        }
+        check("hour", self.hour as i64, 0..=23)?;
+        check("minute", self.minute as i64, 0..=59)?;
+        check("second", self.second as i64, 0..=59)?;
+        check("fraction", self.fraction as i64, 0..=999_999_999)?;
+
         let date = Date {
             year: self.year,
             month: self.month,
@@ -596,16 +2331,1099 @@ impl TryConvert<(time::Date, time::Time)> for Timestamp {
             second: self.second,
         };
         let nanosecond = self.fraction;
-        let time = (time, nanosecond).try_convert()?;
+        let time = (time, nanosecond).try_convert().map_err(|e: time::Error| {
+            OdbcHelperError::TypeConversionError(format!("Time:{}", e))
+        })?;
         Ok((date, time))
     }
 }
 
 impl TryConvert<time::PrimitiveDateTime> for Timestamp {
-    type Error = time::Error;
+    type Error = OdbcHelperError;
 
     fn try_convert(self) -> Result<time::PrimitiveDateTime, Self::Error> {
         let (date, time) = self.try_convert()?;
         Ok(time::PrimitiveDateTime::new(date, time))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures::{self, item};
+
+    #[test]
+    fn test_estimated_size_bytes() {
+        let rows = vec![vec![
+            fixtures::item(OdbcColumnType::Text, Some("hello")),
+            fixtures::item(OdbcColumnType::I32, None),
+        ]];
+        let expected =
+            mem::size_of::<Vec<OdbcColumnItem>>() + 2 * mem::size_of::<OdbcColumnItem>() + 5;
+        assert_eq!(estimated_size_bytes(&rows), expected);
+        assert_eq!(estimated_size_bytes(&[]), 0);
+    }
+
+    #[test]
+    fn test_column_index() {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, false),
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 10 }, true),
+            OdbcColumn::new("id".to_string(), DataType::BigInt, false),
+        ];
+        let index = ColumnIndex::new(&columns);
+        assert_eq!(index.index_of("id"), Some(0));
+        assert_eq!(index.index_of("name"), Some(1));
+        assert_eq!(index.index_of("missing"), None);
+
+        let row = vec![
+            fixtures::item(OdbcColumnType::I32, Some("7")),
+            fixtures::item(OdbcColumnType::Text, Some("foo")),
+        ];
+        let name = index.get(&row, "name").unwrap();
+        assert_eq!(name.value_to_string().unwrap(), "foo");
+    }
+
+    #[test]
+    fn test_numeric_bounds() {
+        let items = vec![
+            item(OdbcColumnType::I64, Some("3")),
+            item(OdbcColumnType::I64, None),
+            item(OdbcColumnType::F64, Some("-1.5")),
+            item(OdbcColumnType::F64, Some("NaN")),
+            item(OdbcColumnType::Text, Some("100")),
+            item(OdbcColumnType::I64, Some("10")),
+        ];
+        assert_eq!(numeric_bounds(&items), Some((-1.5, 10.0)));
+        assert_eq!(numeric_bounds(&items[1..2]), None);
+        assert_eq!(numeric_bounds(&[]), None);
+    }
+
+    #[test]
+    fn test_dedup_rows() {
+        let row = |a: &str, b: Option<&str>| {
+            vec![
+                fixtures::item(OdbcColumnType::I32, Some(a)),
+                fixtures::item(OdbcColumnType::Text, b),
+            ]
+        };
+        let rows = vec![
+            row("1", None),
+            row("2", Some("x")),
+            row("1", None),
+            row("2", Some("x")),
+            row("2", Some("x")),
+            row("3", Some("x")),
+        ];
+        assert_eq!(
+            dedup_rows(rows.clone()),
+            vec![row("1", None), row("2", Some("x")), row("3", Some("x"))]
+        );
+        assert_eq!(
+            dedup_adjacent_rows(rows),
+            vec![
+                row("1", None),
+                row("2", Some("x")),
+                row("1", None),
+                row("2", Some("x")),
+                row("3", Some("x")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_rows_by() {
+        let row = |odbc_type, v: Option<&str>, tag: &str| {
+            vec![
+                fixtures::item(odbc_type, v),
+                fixtures::item(OdbcColumnType::Text, Some(tag)),
+            ]
+        };
+        let tags = |rows: &[Vec<OdbcColumnItem>]| {
+            rows.iter()
+                .map(|r| r[1].value_to_string().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let mut rows = vec![
+            row(OdbcColumnType::I64, Some("10"), "a"),
+            row(OdbcColumnType::I64, None, "b"),
+            row(OdbcColumnType::I64, Some("9"), "c"),
+            row(OdbcColumnType::I64, Some("10"), "d"),
+        ];
+        sort_rows_by(&mut rows, 0, true);
+        assert_eq!(tags(&rows), vec!["c", "a", "d", "b"]);
+        sort_rows_by_with_nulls(&mut rows, 0, false, NullOrder::First);
+        assert_eq!(tags(&rows), vec!["b", "a", "d", "c"]);
+
+        let mut rows = vec![
+            row(OdbcColumnType::Time, Some("10:00:00"), "a"),
+            row(OdbcColumnType::Time, Some("9:30:00"), "b"),
+        ];
+        sort_rows_by(&mut rows, 0, true);
+        assert_eq!(tags(&rows), vec!["b", "a"]);
+    }
+
+    #[test]
+    fn test_cmp_value() {
+        let i64 = |v| item(OdbcColumnType::I64, Some(v));
+        let f64 = |v| item(OdbcColumnType::F64, Some(v));
+
+        assert_eq!(i64("9").cmp_value(&i64("10")), Some(Ordering::Less));
+        assert_eq!(i64("2").cmp_value(&f64("1.5")), Some(Ordering::Greater));
+        assert_eq!(f64("NaN").cmp_value(&f64("1e300")), Some(Ordering::Greater));
+        assert_eq!(
+            item(OdbcColumnType::I64, None).cmp_value(&i64("1")),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            item(OdbcColumnType::I64, None).cmp_value(&item(OdbcColumnType::F32, None)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            item(OdbcColumnType::Text, Some("b"))
+                .cmp_value(&item(OdbcColumnType::WText, Some("a"))),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            item(OdbcColumnType::Date, Some("2022-01-02"))
+                .cmp_value(&item(OdbcColumnType::Date, Some("2021-12-31"))),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            item(OdbcColumnType::Text, Some("1")).cmp_value(&i64("1")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_to_timestamp_tz() {
+        let data_type = DataType::Other {
+            data_type: crate::odbc_api::sys::SqlDataType(SQL_SS_TIMESTAMPOFFSET),
+            column_size: 34,
+            decimal_digits: 7,
+        };
+        assert!(is_timestamp_tz(&data_type));
+        assert!(!is_timestamp_tz(&DataType::Timestamp { precision: 7 }));
+
+        let item = |v| fixtures::item(OdbcColumnType::Text, Some(v));
+        let mut items = vec![
+            item("2022-08-24 15:50:36.1234567 +08:00"),
+            item("2022-08-24 08:00:00-05:30"),
+            fixtures::item(OdbcColumnType::Text, None),
+        ];
+        to_timestamp_tz(&mut items).unwrap();
+        assert!(items
+            .iter()
+            .all(|i| i.odbc_type == OdbcColumnType::TimestampTz));
+        assert_eq!(
+            items[0].value_to_string().unwrap(),
+            "2022-08-24 15:50:36.1234567+08:00"
+        );
+        assert_eq!(
+            items[1].value_to_string().unwrap(),
+            "2022-08-24 08:00:00.0-05:30"
+        );
+        let offset = items[0].as_offset_date_time().unwrap().offset();
+        assert_eq!(offset.whole_hours(), 8);
+        assert_eq!(items[0].cmp_value(&items[1]), Some(Ordering::Less));
+
+        assert!(parse_to_data_time(items[0].value.clone().unwrap()).is_ok());
+        assert!(to_timestamp_tz(&mut [item("2022-08-24")]).is_err());
+    }
+
+    #[test]
+    fn test_diff_rows() {
+        let row = |id: &str, name: Option<&str>| {
+            vec![
+                fixtures::item(OdbcColumnType::I32, Some(id)),
+                fixtures::item(OdbcColumnType::Text, name),
+            ]
+        };
+        let expected = vec![row("1", Some("a")), row("2", None), row("3", Some("c"))];
+        let actual = vec![row("3", Some("c")), row("2", Some("b")), row("4", None)];
+
+        let diff = diff_rows(&expected, &actual, &[0]);
+        assert_eq!(
+            diff,
+            RowDiff {
+                added: vec![2],
+                removed: vec![0],
+                changed: vec![RowChange {
+                    expected_index: 1,
+                    actual_index: 1,
+                    columns: vec![1],
+                }],
+            }
+        );
+        assert!(diff_rows(&expected, &expected, &[0]).is_empty());
+
+        let by_position = diff_rows(&expected, &actual, &[]);
+        assert_eq!(by_position.changed.len(), 3);
+    }
+
+    #[test]
+    fn test_changed_cells() {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, false),
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 20 }, true),
+            OdbcColumn::new("note".to_string(), DataType::Varchar { length: 20 }, true),
+        ];
+        let old = vec![
+            item(OdbcColumnType::I32, Some("1")),
+            item(OdbcColumnType::Text, Some("a")),
+            item(OdbcColumnType::Text, None),
+        ];
+        let new = vec![
+            item(OdbcColumnType::I32, Some("1")),
+            item(OdbcColumnType::Text, Some("b")),
+            item(OdbcColumnType::Text, None),
+        ];
+        assert_eq!(
+            changed_cells(&old, &new, &columns),
+            vec![("name".to_string(), old[1].clone(), new[1].clone())]
+        );
+        assert!(changed_cells(&old, &old, &columns).is_empty());
+    }
+
+    #[test]
+    fn test_to_interval() {
+        let data_type = DataType::Other {
+            data_type: crate::odbc_api::sys::SqlDataType(110),
+            column_size: 0,
+            decimal_digits: 0,
+        };
+        assert!(is_interval(&data_type));
+
+        let mut items = vec![fixtures::item(
+            OdbcColumnType::Text,
+            Some("1 year 2 mons 3 days 04:05:06"),
+        )];
+        to_interval(&mut items).unwrap();
+        assert_eq!(items[0].odbc_type, OdbcColumnType::Interval);
+        assert_eq!(items[0].value_to_string().unwrap(), "P1Y2M3DT4H5M6S");
+        assert_eq!(items[0].as_interval().unwrap().months, 14);
+    }
+
+    #[test]
+    fn test_time_past_24_hours() {
+        let item = time_item(&Time {
+            hour: 30,
+            minute: 15,
+            second: 5,
+        })
+        .unwrap();
+        assert_eq!(item.odbc_type, OdbcColumnType::Interval);
+        assert_eq!(item.value_to_string().as_deref(), Some("PT30H15M5S"));
+
+        let item = time_item(&Time {
+            hour: 23,
+            minute: 59,
+            second: 59,
+        })
+        .unwrap();
+        assert_eq!(item.odbc_type, OdbcColumnType::Time);
+        assert_eq!(item.value_to_string().as_deref(), Some("23:59:59.0"));
+    }
+
+    #[test]
+    fn test_drop_empty_columns() {
+        let item = |v| fixtures::item(OdbcColumnType::Text, v);
+        let mut columns = ["a", "b", "c"]
+            .iter()
+            .map(|name| OdbcColumn::new(name.to_string(), DataType::Varchar { length: 8 }, true))
+            .collect::<Vec<_>>();
+        let mut data = vec![
+            vec![item(Some("1")), item(None), item(None)],
+            vec![item(None), item(None), item(Some("x"))],
+        ];
+        drop_empty_columns(&mut columns, &mut data);
+        assert_eq!(
+            columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+            vec!["a", "c"]
+        );
+        assert_eq!(
+            data,
+            vec![
+                vec![item(Some("1")), item(None)],
+                vec![item(None), item(Some("x"))],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coerce_items() {
+        let mut items = vec![
+            item(OdbcColumnType::Bit, Some("true")),
+            item(OdbcColumnType::Bit, Some("false")),
+            item(OdbcColumnType::Bit, None),
+        ];
+        coerce_items(&mut items, OdbcColumnType::I32).unwrap();
+        assert_eq!(
+            items,
+            vec![
+                item(OdbcColumnType::I32, Some("1")),
+                item(OdbcColumnType::I32, Some("0")),
+                item(OdbcColumnType::I32, None),
+            ]
+        );
+
+        let mut items = vec![item(OdbcColumnType::Text, Some("12.50"))];
+        coerce_items(&mut items, OdbcColumnType::F64).unwrap();
+        assert_eq!(items, vec![item(OdbcColumnType::F64, Some("12.5"))]);
+
+        let mut items = vec![item(OdbcColumnType::Date, Some("2022-08-24"))];
+        coerce_items(&mut items, OdbcColumnType::Text).unwrap();
+        assert_eq!(items, vec![item(OdbcColumnType::Text, Some("2022-08-24"))]);
+
+        let mut items = vec![item(OdbcColumnType::I32, Some("300"))];
+        assert!(coerce_items(&mut items, OdbcColumnType::I8).is_err());
+        let mut items = vec![item(OdbcColumnType::Text, Some("1.5"))];
+        assert!(coerce_items(&mut items, OdbcColumnType::I64).is_err());
+        let mut items = vec![item(OdbcColumnType::Date, Some("2022-08-24"))];
+        assert!(coerce_items(&mut items, OdbcColumnType::I64).is_err());
+
+        assert_eq!(
+            DataTypeKind::from(&DataType::Decimal {
+                precision: 10,
+                scale: 2
+            }),
+            DataTypeKind::Decimal
+        );
+    }
+
+    #[test]
+    fn test_concat_result_sets() {
+        let item = |odbc_type, v| fixtures::item(odbc_type, Some(v));
+        let first = (
+            vec![
+                OdbcColumn::new("id".to_string(), DataType::Integer, false),
+                OdbcColumn::new("name".to_string(), DataType::Varchar { length: 10 }, false),
+            ],
+            vec![vec![
+                item(OdbcColumnType::I32, "1"),
+                item(OdbcColumnType::Text, "a"),
+            ]],
+        );
+        let second = (
+            vec![
+                OdbcColumn::new("ID".to_string(), DataType::BigInt, false),
+                OdbcColumn::new("NAME".to_string(), DataType::WVarchar { length: 50 }, true),
+            ],
+            vec![vec![
+                item(OdbcColumnType::I64, "2"),
+                item(OdbcColumnType::WText, "b"),
+            ]],
+        );
+        let (columns, data) = concat_result_sets(vec![first.clone(), second]).unwrap();
+        assert_eq!(columns[0].name, "id");
+        assert!(columns[1].nullable);
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[1][0], item(OdbcColumnType::I64, "2"));
+
+        let mismatch = (
+            vec![
+                OdbcColumn::new("id".to_string(), DataType::Integer, false),
+                OdbcColumn::new("created".to_string(), DataType::Date, false),
+            ],
+            vec![],
+        );
+        let error = concat_result_sets(vec![first.clone(), mismatch]).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("`created DATE NOT NULL` doesn't match `name VARCHAR(10) NOT NULL`"));
+        let narrow = (first.0[..1].to_vec(), vec![]);
+        assert!(concat_result_sets(vec![first, narrow]).is_err());
+    }
+
+    #[test]
+    fn test_column_display() {
+        let column = OdbcColumn::new("id".to_string(), DataType::Integer, false);
+        assert_eq!(column.to_string(), "id INTEGER NOT NULL");
+        let column = OdbcColumn::new("note".to_string(), DataType::Varchar { length: 255 }, true);
+        assert_eq!(column.to_string(), "note VARCHAR(255)");
+    }
+
+    #[test]
+    fn test_round_to_scale() {
+        let mut items = vec![
+            item(OdbcColumnType::F64, Some(&(0.1f64 + 0.2).to_string())),
+            item(OdbcColumnType::F32, Some("2.675")),
+            item(OdbcColumnType::F64, Some("NaN")),
+            item(OdbcColumnType::F64, None),
+            item(OdbcColumnType::Text, Some("1.23456")),
+        ];
+        let changed = round_to_scale(&mut items, 2);
+        assert_eq!(
+            changed.iter().map(|c| c.row).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        assert!(changed[1].delta() > 0.004);
+
+        let report = ConversionReport::new(0.001);
+        report.record_rounded("price", 10, &changed);
+        let rounded = report.clone().rounded();
+        assert_eq!(rounded.len(), 1);
+        assert_eq!((rounded[0].0.as_str(), rounded[0].1.row), ("price", 11));
+        assert_eq!(
+            items,
+            vec![
+                item(OdbcColumnType::F64, Some("0.3")),
+                item(OdbcColumnType::F32, Some("2.67")),
+                item(OdbcColumnType::F64, Some("NaN")),
+                item(OdbcColumnType::F64, None),
+                item(OdbcColumnType::Text, Some("1.23456")),
+            ]
+        );
+        assert_eq!(declared_scale(&DataType::Double), None);
+        assert_eq!(
+            declared_scale(&DataType::Numeric {
+                precision: 10,
+                scale: 4
+            }),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn test_normalize_tinyint() {
+        let signed = |v| fixtures::item(OdbcColumnType::I8, Some(v));
+        let unsigned = |v: u8| fixtures::bytes_item(OdbcColumnType::U8, Some(&[v]));
+        let i16 = |v| fixtures::item(OdbcColumnType::I16, v);
+        let null = fixtures::item(OdbcColumnType::U8, None);
+
+        let mut items = vec![unsigned(200), signed("-56"), null.clone()];
+        normalize_tinyint(&mut items, TinyIntSign::Unsigned);
+        assert_eq!(items, vec![i16(Some("200")), i16(Some("200")), i16(None)]);
+
+        let mut items = vec![signed("-50"), unsigned(206), null];
+        normalize_tinyint(&mut items, TinyIntSign::Signed);
+        assert_eq!(items, vec![i16(Some("-50")), i16(Some("-50")), i16(None)]);
+    }
+
+    #[test]
+    fn test_validate_row() {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::BigInt, false),
+            OdbcColumn::new("price".to_string(), DataType::Double, true),
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 10 }, true),
+        ];
+
+        let row = vec![
+            item(OdbcColumnType::I32, Some("1")),
+            item(OdbcColumnType::I32, Some("10")),
+            item(OdbcColumnType::Text, None),
+        ];
+        validate_row(&columns, &row).unwrap();
+
+        let error = validate_row(&columns, &row[..2]).unwrap_err();
+        assert!(error.to_string().contains("2 values for 3 columns"));
+
+        let mut null_id = row.clone();
+        null_id[0] = item(OdbcColumnType::I64, None);
+        let error = validate_row(&columns, &null_id).unwrap_err();
+        assert!(error.to_string().contains("NULL for `id BIGINT NOT NULL`"));
+
+        let mut text_price = row;
+        text_price[1] = item(OdbcColumnType::Text, Some("cheap"));
+        let error = validate_row(&columns, &text_price).unwrap_err();
+        assert!(error.to_string().contains("Text value for `price DOUBLE`"));
+
+        assert!(!is_compatible(OdbcColumnType::I64, &DataType::Integer));
+        assert!(is_compatible(
+            OdbcColumnType::Date,
+            &DataType::Timestamp { precision: 0 }
+        ));
+    }
+
+    #[test]
+    fn test_to_bit() {
+        let item = |odbc_type, v| fixtures::bytes_item(odbc_type, v);
+        let mut items = vec![
+            item(OdbcColumnType::U8, Some(&[1])),
+            item(OdbcColumnType::U8, Some(&[0])),
+            item(OdbcColumnType::U8, None),
+            item(OdbcColumnType::Bit, Some(b"true")),
+        ];
+        to_bit(&mut items);
+        assert_eq!(
+            items,
+            vec![
+                item(OdbcColumnType::Bit, Some(b"true")),
+                item(OdbcColumnType::Bit, Some(b"false")),
+                item(OdbcColumnType::Bit, None),
+                item(OdbcColumnType::Bit, Some(b"true")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_names() {
+        let mut columns = ["id", "", "id", " ", "column_2", "id_3"]
+            .iter()
+            .map(|name| OdbcColumn::new(name.to_string(), DataType::Integer, true))
+            .collect::<Vec<_>>();
+        normalize_names(&mut columns);
+        assert_eq!(
+            columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+            vec!["id", "column_2", "id_3", "column_4", "column_2_5", "id_3_6"]
+        );
+    }
+
+    #[test]
+    fn test_date_year_boundaries() {
+        let date = |year, month, day| -> Result<time::Date, OdbcHelperError> {
+            Date { year, month, day }.try_convert()
+        };
+        assert_eq!(date(0, 1, 1).unwrap().to_string(), "0000-01-01");
+        assert_eq!(date(-44, 3, 15).unwrap().year(), -44);
+        assert_eq!(date(9999, 12, 31).unwrap().year(), 9999);
+
+        for (year, month, day) in [(0, 0, 0), (2022, 2, 30), (10000, 1, 1), (2022, 13, 1)] {
+            assert!(matches!(
+                date(year, month, day),
+                Err(OdbcHelperError::InvalidDate { .. })
+            ));
+        }
+        assert_eq!(
+            date(0, 0, 0).unwrap_err().to_string(),
+            "invalid date `0000-00-00`, supported years are -9999 to 9999"
+        );
+    }
+
+    #[test]
+    fn test_text_buffer_policy() {
+        let desc = |data_type, options: &Options| {
+            let column = OdbcColumn::new("c".to_string(), data_type, true);
+            <(&OdbcColumn, &Options) as TryConvert<BufferDesc>>::try_convert((&column, options))
+                .unwrap()
+        };
+        let mut options = Options::new(crate::executor::SupportDatabase::Pg);
+        let (narrow, wide) = (
+            DataType::Varchar { length: 10 },
+            DataType::WVarchar { length: 10 },
+        );
+        assert_eq!(
+            desc(narrow, &options),
+            BufferDesc::WText { max_str_len: 10 }
+        );
+
+        options.text_buffers = TextBufferPolicy::ByDataType;
+        assert_eq!(desc(narrow, &options), BufferDesc::Text { max_str_len: 10 });
+        assert_eq!(desc(wide, &options), BufferDesc::WText { max_str_len: 10 });
+        assert_eq!(
+            desc(DataType::Integer, &options),
+            BufferDesc::I32 { nullable: true }
+        );
+    }
+
+    #[test]
+    fn test_scalar() {
+        let columns = vec![OdbcColumn::new(
+            "count".to_string(),
+            DataType::BigInt,
+            false,
+        )];
+        let item = fixtures::item(OdbcColumnType::I64, Some("42"));
+        assert_eq!(
+            scalar(&columns, vec![vec![item.clone()]]).unwrap(),
+            item.clone()
+        );
+        assert!(matches!(
+            scalar(&columns, vec![]),
+            Err(OdbcHelperError::NotScalar {
+                rows: 0,
+                columns: 1
+            })
+        ));
+        assert!(scalar(&columns, vec![vec![item.clone()], vec![item]]).is_err());
+    }
+
+    #[test]
+    fn test_strip_bom() {
+        let item = |odbc_type, v| fixtures::item(odbc_type, Some(v));
+        let mut items = vec![
+            item(OdbcColumnType::Text, "\u{FEFF}key"),
+            item(OdbcColumnType::WText, "\u{FEFF}\u{FEFF}x"),
+            item(OdbcColumnType::Text, "no bom"),
+            item(OdbcColumnType::Binary, "\u{FEFF}raw"),
+        ];
+        strip_bom(&mut items);
+        let texts = items
+            .iter()
+            .map(|i| i.value_to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(texts[0].as_deref(), Some("key"));
+        assert_eq!(texts[1].as_deref(), Some("\u{FEFF}x"));
+        assert_eq!(texts[2].as_deref(), Some("no bom"));
+        assert_eq!(items[3].value.as_deref(), Some("\u{FEFF}raw".as_bytes()));
+    }
+
+    #[test]
+    fn test_column_try_new() {
+        let column = OdbcColumn::try_new("id".to_string(), DataType::Integer, false).unwrap();
+        assert_eq!(column.name, "id");
+        for name in ["", "  \t"] {
+            assert!(matches!(
+                OdbcColumn::try_new(name.to_string(), DataType::Integer, false),
+                Err(OdbcHelperError::InvalidColumnName(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_collapse_midnight_timestamps() {
+        let mut items = vec![
+            item(OdbcColumnType::Timestamp, Some("2022-01-02 0:00:00.0")),
+            item(OdbcColumnType::Timestamp, Some("2022-01-02 0:00:00.001")),
+            item(OdbcColumnType::Timestamp, None),
+            item(OdbcColumnType::Text, Some("2022-01-02 0:00:00.0")),
+        ];
+        collapse_midnight_timestamps(&mut items);
+        assert_eq!(items[0], item(OdbcColumnType::Date, Some("2022-01-02")));
+        assert_eq!(items[1].odbc_type, OdbcColumnType::Timestamp);
+        assert_eq!(items[2], item(OdbcColumnType::Timestamp, None));
+        assert_eq!(items[3].odbc_type, OdbcColumnType::Text);
+    }
+
+    #[test]
+    fn test_hex_text_to_binary() {
+        let item = |v| fixtures::item(OdbcColumnType::Text, v);
+        let mut items = vec![item(Some("0xDEADbeef")), item(Some("0102")), item(None)];
+        hex_text_to_binary(&mut items).unwrap();
+        assert!(items.iter().all(|i| i.odbc_type == OdbcColumnType::Binary));
+        assert_eq!(
+            items[0].value.as_deref(),
+            Some(&[0xde, 0xad, 0xbe, 0xef][..])
+        );
+        assert_eq!(items[1].value.as_deref(), Some(&[0x01, 0x02][..]));
+        assert_eq!(items[2].value, None);
+
+        for bad in ["0x123", "zz", "0x+1"] {
+            assert!(hex_text_to_binary(&mut [item(Some(bad))]).is_err());
+        }
+        assert!(is_binary(&DataType::Varbinary { length: 4 }));
+    }
+
+    #[test]
+    fn test_sql_value_round_trip() {
+        for (odbc_type, v, expected) in [
+            (OdbcColumnType::I16, "-7", SqlValue::Int(-7)),
+            (OdbcColumnType::F32, "1.5", SqlValue::Float(1.5)),
+            (OdbcColumnType::Bit, "true", SqlValue::Bool(true)),
+            (
+                OdbcColumnType::WText,
+                "wide",
+                SqlValue::Text("wide".to_string()),
+            ),
+            (
+                OdbcColumnType::TimestampTz,
+                "2022-08-24 15:50:36.5+08:00",
+                SqlValue::Timestamp("2022-08-24 15:50:36.5+08:00".to_string()),
+            ),
+        ] {
+            assert_eq!(SqlValue::from(item(odbc_type, Some(v))), expected);
+        }
+        assert_eq!(
+            SqlValue::from(item(OdbcColumnType::U8, None)),
+            SqlValue::Null
+        );
+        assert_eq!(
+            SqlValue::from(fixtures::bytes_item(OdbcColumnType::U8, Some(&[200u8]))),
+            SqlValue::Int(200)
+        );
+
+        for original in [
+            item(OdbcColumnType::I64, Some("42")),
+            item(OdbcColumnType::Text, Some("00123")),
+            item(OdbcColumnType::Date, Some("2022-01-02")),
+            item(OdbcColumnType::Timestamp, Some("2022-01-02 3:04:05.5")),
+            item(
+                OdbcColumnType::TimestampTz,
+                Some("2022-08-24 15:50:36.5+08:00"),
+            ),
+            item(OdbcColumnType::Text, None),
+        ] {
+            let value = SqlValue::from(original.clone());
+            assert_eq!(OdbcColumnItem::try_from(value).unwrap(), original);
+        }
+        assert!(OdbcColumnItem::try_from(SqlValue::Date("2022-13-01".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_field_ranges() {
+        let valid = Timestamp {
+            year: 2024,
+            month: 2,
+            day: 29,
+            hour: 23,
+            minute: 59,
+            second: 59,
+            fraction: 999_999_999,
+        };
+        let converted: time::PrimitiveDateTime = valid.try_convert().unwrap();
+        assert_eq!(converted.to_string(), "2024-02-29 23:59:59.999999999");
+
+        let cases: [(Timestamp, &str, i64); 10] = [
+            (
+                Timestamp {
+                    year: 10000,
+                    ..valid
+                },
+                "year",
+                10000,
+            ),
+            (Timestamp { month: 0, ..valid }, "month", 0),
+            (Timestamp { month: 13, ..valid }, "month", 13),
+            (Timestamp { day: 0, ..valid }, "day", 0),
+            (
+                Timestamp {
+                    day: 32,
+                    month: 1,
+                    ..valid
+                },
+                "day",
+                32,
+            ),
+            (
+                Timestamp {
+                    year: 2023,
+                    ..valid
+                },
+                "day",
+                29,
+            ),
+            (Timestamp { hour: 24, ..valid }, "hour", 24),
+            (
+                Timestamp {
+                    minute: 60,
+                    ..valid
+                },
+                "minute",
+                60,
+            ),
+            (
+                Timestamp {
+                    second: 60,
+                    ..valid
+                },
+                "second",
+                60,
+            ),
+            (
+                Timestamp {
+                    fraction: 1_000_000_000,
+                    ..valid
+                },
+                "fraction",
+                1_000_000_000,
+            ),
+        ];
+        for (timestamp, expected_field, expected_value) in cases {
+            let result: Result<time::PrimitiveDateTime, _> = timestamp.try_convert();
+            match result {
+                Err(OdbcHelperError::InvalidTimestamp { field, value }) => {
+                    assert_eq!((field, value), (expected_field, expected_value))
+                }
+                other => panic!("{:?} for {:?}", other, timestamp),
+            }
+        }
+        let err: Result<time::PrimitiveDateTime, _> = Timestamp { hour: 24, ..valid }.try_convert();
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "invalid timestamp, `hour` 24 is out of range"
+        );
+    }
+
+    #[test]
+    fn test_infer_nullability() {
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, true),
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 20 }, false),
+        ];
+        let item = |v| fixtures::item(OdbcColumnType::Text, v);
+        let rows = vec![
+            vec![item(Some("1")), item(Some("a"))],
+            vec![item(Some("2")), item(None)],
+        ];
+        assert_eq!(infer_nullability(&columns, &rows), vec![false, true]);
+        assert_eq!(infer_nullability(&columns, &[]), vec![false, false]);
+    }
+
+    #[test]
+    fn test_to_unsigned_bigint() {
+        let mut column = OdbcColumn::new("id".to_string(), DataType::BigInt, false);
+        column.unsigned = Some(true);
+        let options = Options::new(crate::executor::SupportDatabase::Mysql);
+        let desc =
+            <(&OdbcColumn, &Options) as TryConvert<BufferDesc>>::try_convert((&column, &options));
+        assert_eq!(desc, Ok(BufferDesc::Text { max_str_len: 20 }));
+
+        let big = u64::MAX - 1;
+        // the bits of `big` in a 64-bit buffer read as signed
+        let wrapped = (big as i64).to_string();
+        let mut items = vec![
+            item(OdbcColumnType::I64, Some(&wrapped)),
+            item(OdbcColumnType::Text, Some("18446744073709551615")),
+            item(OdbcColumnType::I64, None),
+        ];
+        to_unsigned_bigint(&mut items).unwrap();
+        assert_eq!(
+            items,
+            vec![
+                item(OdbcColumnType::U64, Some("18446744073709551614")),
+                item(OdbcColumnType::U64, Some("18446744073709551615")),
+                item(OdbcColumnType::U64, None),
+            ]
+        );
+        assert!(to_unsigned_bigint(&mut [item(OdbcColumnType::Text, Some("-1"))]).is_err());
+    }
+
+    #[test]
+    fn test_count_true() {
+        let items = vec![
+            item(OdbcColumnType::Bit, Some("true")),
+            item(OdbcColumnType::Bit, Some("false")),
+            item(OdbcColumnType::Bit, None),
+            item(OdbcColumnType::Bit, Some("true")),
+        ];
+        assert_eq!(items[1].bit_as_int(), Some(0));
+        assert_eq!(items[2].bit_as_int(), None);
+        assert_eq!(item(OdbcColumnType::I32, Some("1")).bit_as_int(), None);
+        assert_eq!(count_true(&items), 2);
+    }
+
+    #[test]
+    fn test_widen_unsigned_u8() {
+        let byte = |b: u8| fixtures::bytes_item(OdbcColumnType::U8, Some(&[b]));
+        let mut items = vec![byte(0), byte(255)];
+        widen_to_i64(&mut items);
+        let values = items.iter().map(|i| i.as_i64()).collect::<Vec<_>>();
+        assert_eq!(values, vec![Some(0), Some(255)]);
+        assert!(items.iter().all(|i| i.odbc_type == OdbcColumnType::I64));
+
+        assert!(coerce_items(&mut [byte(255)], OdbcColumnType::I8).is_err());
+        let mut items = vec![byte(0), byte(255)];
+        coerce_items(&mut items, OdbcColumnType::I16).unwrap();
+        assert_eq!(items[1].value_to_string().as_deref(), Some("255"));
+
+        assert!(is_compatible(OdbcColumnType::U8, &DataType::SmallInt));
+    }
+
+    #[test]
+    fn test_time_fraction_digits() {
+        let odbc_time = Time {
+            hour: 12,
+            minute: 30,
+            second: 15,
+        };
+        let column = DataType::Time { precision: 3 };
+        let digits = fraction_digits(&column).unwrap();
+        let t: time::Time = (odbc_time, 123_456_789, digits).try_convert().unwrap();
+        assert_eq!(t.nanosecond(), 123_000_000);
+        let t: time::Time = (odbc_time, 999_999_999, 0).try_convert().unwrap();
+        assert_eq!((t.second(), t.nanosecond()), (15, 0));
+        let t: time::Time = (odbc_time, 123_456_789, 12).try_convert().unwrap();
+        assert_eq!(t.nanosecond(), 123_456_789);
+        assert_eq!(fraction_digits(&DataType::Integer), None);
+
+        let column = OdbcColumn::new(
+            "at".to_string(),
+            DataType::Timestamp { precision: 3 },
+            false,
+        );
+        let timestamps = [Timestamp {
+            year: 2022,
+            month: 1,
+            day: 2,
+            hour: 3,
+            minute: 4,
+            second: 5,
+            fraction: 123_456_789,
+        }];
+        let items = try_convert_view(AnySlice::Timestamp(&timestamps), &column).unwrap();
+        assert_eq!(
+            items[0].value_to_string().as_deref(),
+            Some("2022-01-02 3:04:05.123")
+        );
+        let items = AnySlice::Timestamp(&timestamps).convert();
+        assert_eq!(
+            items[0].value_to_string().as_deref(),
+            Some("2022-01-02 3:04:05.123456789")
+        );
+    }
+
+    #[test]
+    fn test_distinct_values() {
+        let item = |v| fixtures::item(OdbcColumnType::Text, v);
+        let items = vec![
+            item(Some("b")),
+            item(None),
+            item(Some("a")),
+            item(Some("b")),
+            item(None),
+            item(Some("c")),
+        ];
+        assert_eq!(
+            distinct_values(&items, 10),
+            vec![
+                item(Some("b")),
+                item(None),
+                item(Some("a")),
+                item(Some("c"))
+            ]
+        );
+        assert_eq!(
+            distinct_values(&items, 2),
+            vec![item(Some("b")), item(None)]
+        );
+        assert_eq!(distinct_count_bounded(&items, 10), 4);
+        assert_eq!(distinct_count_bounded(&items, 3), 3);
+        assert_eq!(distinct_count_bounded(&items, 0), 0);
+    }
+
+    #[test]
+    fn test_chunk_columns() {
+        let columns = ["id", "a", "b", "c"]
+            .iter()
+            .map(|name| OdbcColumn::new(name.to_string(), DataType::Integer, true))
+            .collect::<Vec<_>>();
+        let row = ["1", "2", "3", "4"]
+            .iter()
+            .map(|v| fixtures::item(OdbcColumnType::I32, Some(*v)))
+            .collect::<Vec<_>>();
+        let data = vec![row];
+        let names = |chunks: &[(Vec<OdbcColumn>, Vec<Vec<OdbcColumnItem>>)]| {
+            chunks
+                .iter()
+                .map(|(columns, _)| columns.iter().map(|c| c.name.clone()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+
+        let chunks = chunk_columns(&columns, &data, 2, &[0]);
+        assert_eq!(names(&chunks), vec![vec!["id", "a", "b"], vec!["id", "c"]]);
+        assert_eq!(chunks[1].1[0][1].value_to_string().as_deref(), Some("4"));
+
+        let chunks = chunk_columns(&columns, &data, 3, &[]);
+        assert_eq!(names(&chunks), vec![vec!["id", "a", "b"], vec!["c"]]);
+        assert!(chunk_columns(&[], &[], 3, &[0]).is_empty());
+    }
+
+    #[test]
+    fn test_multi_bit_columns() {
+        let options = Options::new(crate::executor::SupportDatabase::Mysql);
+        let desc = |n| {
+            let mut column = OdbcColumn::new("flags".to_string(), DataType::Bit, true);
+            column.declared_length = Some(n);
+            <(&OdbcColumn, &Options) as TryConvert<BufferDesc>>::try_convert((&column, &options))
+                .unwrap()
+        };
+        assert_eq!(desc(1), BufferDesc::Bit { nullable: true });
+        assert_eq!(desc(8), BufferDesc::Binary { length: 1 });
+        assert_eq!(desc(16), BufferDesc::Binary { length: 2 });
+
+        let binary = |bytes: &[u8]| fixtures::bytes_item(OdbcColumnType::Binary, Some(bytes));
+        let mut items = vec![binary(&[0xa5]), binary(&[0x00])];
+        bits_to_integer(&mut items, 8).unwrap();
+        assert_eq!(items[0].as_i64(), Some(165));
+        assert_eq!(items[1].as_i64(), Some(0));
+
+        let mut items = vec![binary(&[0x01, 0x00]), binary(&[0xff, 0xff])];
+        bits_to_integer(&mut items, 16).unwrap();
+        assert_eq!(items[0].as_i64(), Some(256));
+        assert_eq!(items[1].as_i64(), Some(65_535));
+        assert_eq!(items[1].odbc_type, OdbcColumnType::I64);
+
+        let mut items = vec![binary(&[0xff; 8])];
+        bits_to_integer(&mut items, 64).unwrap();
+        assert_eq!(
+            items[0].value_to_string().as_deref(),
+            Some("18446744073709551615")
+        );
+    }
+
+    #[test]
+    fn test_try_convert_view() {
+        let column = OdbcColumn::new("born".to_string(), DataType::Date, false);
+        let dates = [
+            Date {
+                year: 2022,
+                month: 1,
+                day: 2,
+            },
+            Date {
+                year: 0,
+                month: 0,
+                day: 0,
+            },
+        ];
+        let err = try_convert_view(AnySlice::Date(&dates[1..]), &column).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to convert column `born`: invalid date `0000-00-00`, supported years are -9999 to 9999"
+        );
+        let items = try_convert_view(AnySlice::Date(&dates[..1]), &column).unwrap();
+        assert_eq!(items[0].value_to_string().unwrap(), "2022-01-02");
+
+        let times = [Time {
+            hour: 10,
+            minute: 60,
+            second: 0,
+        }];
+        assert!(try_convert_view(AnySlice::Time(&times), &column).is_err());
+    }
+
+    #[test]
+    fn test_check_buffer_kind() {
+        let column = OdbcColumn::new("id".to_string(), DataType::BigInt, false);
+        let item = |odbc_type| fixtures::item(odbc_type, Some("1"));
+        assert!(check_buffer_kind(&[item(OdbcColumnType::I64)], &column).is_ok());
+        assert!(check_buffer_kind(&[item(OdbcColumnType::Text)], &column).is_ok());
+        let err = check_buffer_kind(&[item(OdbcColumnType::F64)], &column).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "column `id` is described as BigInt but returned F64 cells"
+        );
+
+        let column = OdbcColumn::new("raw".to_string(), DataType::Varbinary { length: 4 }, true);
+        assert!(check_buffer_kind(&[item(OdbcColumnType::Text)], &column).is_err());
+        assert!(is_expected_kind(
+            &DataType::Time { precision: 0 },
+            OdbcColumnType::Interval
+        ));
+    }
+
+    #[test]
+    fn test_empty_wide_text() {
+        use crate::odbc_api::buffers::{AnyBuffer, ColumnBuffer};
+
+        let mut buffer = AnyBuffer::from_desc(2, BufferDesc::WText { max_str_len: 4 });
+        if let AnyBuffer::WText(column) = &mut buffer {
+            column.set_value(0, Some(&[]));
+            column.set_value(1, None);
+        }
+        let column = OdbcColumn::new("s".to_string(), DataType::WVarchar { length: 4 }, true);
+        let items = try_convert_view(buffer.view(2), &column).unwrap();
+        assert_eq!(items[0].odbc_type, OdbcColumnType::WText);
+        assert_eq!(items[0].value, Some(BytesMut::new()));
+        assert_eq!(items[0].value_to_string().as_deref(), Some(""));
+        assert_eq!(items[1].value, None);
+    }
+
+    #[test]
+    fn test_as_string() {
+        let item = |odbc_type, v| fixtures::bytes_item(odbc_type, v);
+        assert_eq!(
+            item(OdbcColumnType::Text, Some(b"abc")).as_string(),
+            Some("abc")
+        );
+        assert_eq!(
+            item(OdbcColumnType::WText, Some("\u{4e16}".as_bytes())).as_string(),
+            Some("\u{4e16}")
+        );
+        assert_eq!(item(OdbcColumnType::WText, Some(b"")).as_string(), Some(""));
+        assert_eq!(item(OdbcColumnType::Text, None).as_string(), None);
+        assert_eq!(item(OdbcColumnType::Text, Some(&[0xff])).as_string(), None);
+        assert_eq!(item(OdbcColumnType::I32, Some(b"1")).as_string(), None);
+    }
+}
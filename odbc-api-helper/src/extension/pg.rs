@@ -6,7 +6,7 @@ use crate::odbc_api::buffers::BufferDesc;
 use crate::odbc_api::parameter::InputParameter;
 use crate::odbc_api::Bit;
 use crate::odbc_api::IntoParameter;
-use crate::{Convert, TryConvert};
+use crate::{ConversionMode, Convert, TryConvert};
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use either::Either;
 use pg_helper::table::PgTableItem;
@@ -19,6 +19,7 @@ use crate::executor::SupportDatabase;
 use crate::extension::util::{
     parse_to_bool, parse_to_data_time, parse_to_date, parse_to_float4, parse_to_float8,
     parse_to_i8, parse_to_int2, parse_to_int4, parse_to_int8, parse_to_string, parse_to_time,
+    parse_to_utc_date_time,
 };
 use dameng_helper::table::DmTableDesc;
 
@@ -134,7 +135,12 @@ impl Convert<PgColumn> for OdbcColumn {
 impl Convert<PgColumnItem> for OdbcColumnItem {
     fn convert(self) -> PgColumnItem {
         let value = match self.odbc_type {
-            OdbcColumnType::Text | OdbcColumnType::WText => self
+            OdbcColumnType::Text
+            | OdbcColumnType::WText
+            | OdbcColumnType::Array
+            | OdbcColumnType::Json
+            | OdbcColumnType::Interval
+            | OdbcColumnType::U64 => self
                 .value
                 .map(|x| PgValueInput::Text(Some(parse_to_string(x)))),
             OdbcColumnType::Binary => self.value.map(|x| PgValueInput::Bytea(Some(x.to_vec()))),
@@ -147,6 +153,21 @@ impl Convert<PgColumnItem> for OdbcColumnItem {
             OdbcColumnType::Timestamp => self
                 .value
                 .map(|x| PgValueInput::Timestamp(Some(parse_to_data_time(x).unwrap()))),
+            // The instant as UTC. Unlike the other conversions here it can't panic, text that
+            // isn't a timestamp with an offset is passed on as text
+            OdbcColumnType::TimestampTz => {
+                self.value.map(|x| match parse_to_utc_date_time(x.clone()) {
+                    Ok(v) => PgValueInput::Timestampz(Some(v)),
+                    Err(e) => {
+                        error!(
+                            "convert TimestampTz `{}` error:{}",
+                            parse_to_string(x.clone()),
+                            e
+                        );
+                        PgValueInput::Text(Some(parse_to_string(x)))
+                    }
+                })
+            }
             OdbcColumnType::F64 => self
                 .value
                 .map(|x| PgValueInput::Float8(Some(parse_to_float8(x).unwrap()))),
@@ -198,6 +219,15 @@ impl Convert<PgType> for PgType {
     }
 }
 
+/// Names of the `JSON`/`JSONB` columns, to pass to [`QueryResult::tag_json_columns`].
+pub fn pg_json_columns(pg_columns: &[PgColumn]) -> Vec<String> {
+    pg_columns
+        .iter()
+        .filter(|c| matches!(c.pg_type, PgType::JSON | PgType::JSONB))
+        .map(|c| c.name.clone())
+        .collect()
+}
+
 pub fn oid_typlen<C: Convert<PgType>>(c: C) -> i16 {
     let pg_type = c.convert();
     pg_helper::oid_typlen(pg_type)
@@ -234,35 +264,38 @@ impl TryConvert<PgColumnItem> for (&OdbcColumnItem, &PgColumn) {
         let odbc_data = self.0.value.clone();
         let pg_column = self.1;
 
+        macro_rules! parse_value {
+            ($parse:ident, $variant:ident) => {{
+                odbc_data
+                    .map($parse)
+                    .transpose()
+                    .map_err(|e| {
+                        format!(
+                            "convert column `{}` to {} error:{}",
+                            pg_column.name, pg_column.pg_type, e
+                        )
+                    })?
+                    .map(|v| PgValueInput::$variant(Some(v)))
+            }};
+        }
+
         let value = match pg_column.pg_type {
             PgType::TEXT => odbc_data.map(|v| PgValueInput::Text(Some(parse_to_string(v)))),
             PgType::VARCHAR => odbc_data.map(|v| PgValueInput::Varchar(Some(parse_to_string(v)))),
             PgType::BYTEA => odbc_data.map(|v| PgValueInput::Bytea(Some(v.to_vec()))),
-            PgType::DATE => odbc_data.map(|v| PgValueInput::Date(Some(parse_to_date(v).unwrap()))),
-            PgType::TIME => odbc_data.map(|v| PgValueInput::Time(Some(parse_to_time(v).unwrap()))),
-            PgType::TIMETZ => {
-                odbc_data.map(|v| PgValueInput::Timez(Some(parse_to_time(v).unwrap())))
-            }
-            PgType::TIMESTAMP => {
-                odbc_data.map(|v| PgValueInput::Timestamp(Some(parse_to_data_time(v).unwrap())))
-            }
-            PgType::TIMESTAMPTZ => {
-                odbc_data.map(|v| PgValueInput::Timestampz(Some(parse_to_data_time(v).unwrap())))
-            }
-            PgType::FLOAT8 => {
-                odbc_data.map(|v| PgValueInput::Float8(Some(parse_to_float8(v).unwrap())))
-            }
-            PgType::FLOAT4 => {
-                odbc_data.map(|v| PgValueInput::Float4(Some(parse_to_float4(v).unwrap())))
-            }
-            PgType::CHAR => odbc_data.map(|v| PgValueInput::Char(Some(parse_to_i8(v).unwrap()))),
-            PgType::INT2 => odbc_data.map(|v| PgValueInput::Int2(Some(parse_to_int2(v).unwrap()))),
-            PgType::INT4 => odbc_data.map(|v| PgValueInput::Int4(Some(parse_to_int4(v).unwrap()))),
-            PgType::NUMERIC => {
-                odbc_data.map(|v| PgValueInput::Numeric(Some(parse_to_int4(v).unwrap())))
-            }
-            PgType::INT8 => odbc_data.map(|v| PgValueInput::Int8(Some(parse_to_int8(v).unwrap()))),
-            PgType::BOOL => odbc_data.map(|v| PgValueInput::Bool(Some(parse_to_bool(v).unwrap()))),
+            PgType::DATE => parse_value!(parse_to_date, Date),
+            PgType::TIME => parse_value!(parse_to_time, Time),
+            PgType::TIMETZ => parse_value!(parse_to_time, Timez),
+            PgType::TIMESTAMP => parse_value!(parse_to_data_time, Timestamp),
+            PgType::TIMESTAMPTZ => parse_value!(parse_to_utc_date_time, Timestampz),
+            PgType::FLOAT8 => parse_value!(parse_to_float8, Float8),
+            PgType::FLOAT4 => parse_value!(parse_to_float4, Float4),
+            PgType::CHAR => parse_value!(parse_to_i8, Char),
+            PgType::INT2 => parse_value!(parse_to_int2, Int2),
+            PgType::INT4 => parse_value!(parse_to_int4, Int4),
+            PgType::NUMERIC => parse_value!(parse_to_int4, Numeric),
+            PgType::INT8 => parse_value!(parse_to_int8, Int8),
+            PgType::BOOL => parse_value!(parse_to_bool, Bool),
             _ => {
                 error!(
                     "There is no adaptation for this type, {}",
@@ -276,37 +309,83 @@ impl TryConvert<PgColumnItem> for (&OdbcColumnItem, &PgColumn) {
     }
 }
 
+/// A row that failed to convert during a batch conversion.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PgRowError {
+    /// index of the row in the source `QueryResult`
+    pub row_index: usize,
+    pub message: String,
+}
+
+/// Batch conversion result, see [`ConversionMode`].
+#[derive(Debug, Default, PartialEq)]
+pub struct PgConversion {
+    /// The successfully converted rows.
+    pub result: PgQueryResult,
+    /// Number of rows dropped because they failed to convert.
+    pub skipped: usize,
+    /// Row errors, only recorded with `ConversionMode::Collect`.
+    pub errors: Vec<PgRowError>,
+}
+
 impl TryConvert<PgQueryResult> for (QueryResult, &Vec<PgTableItem>, &Options) {
     type Error = String;
 
     fn try_convert(self) -> Result<PgQueryResult, Self::Error> {
+        let conversion: PgConversion =
+            (self.0, self.1, self.2, ConversionMode::FailFast).try_convert()?;
+        Ok(conversion.result)
+    }
+}
+
+impl TryConvert<PgConversion> for (QueryResult, &Vec<PgTableItem>, &Options, ConversionMode) {
+    type Error = String;
+
+    fn try_convert(self) -> Result<PgConversion, Self::Error> {
         let res = self.0;
         let pg_all_columns = self.1;
         let options = self.2;
-        let mut result = PgQueryResult::default();
+        let mode = self.3;
+        let mut conversion = PgConversion::default();
         if let Ok(cols) = (&res.columns, pg_all_columns, options).try_convert() {
             let cols: Vec<PgColumn> = cols;
-            result.columns = cols;
+            conversion.result.columns = cols;
 
             // if column name is count(*),but this name not exist Vec<PgTableItem>
             // So,could find result.columns is empty.
-            if result.columns.is_empty() {
-                return Ok(PgQueryResult::from(res));
+            if conversion.result.columns.is_empty() {
+                conversion.result = PgQueryResult::from(res);
+                return Ok(conversion);
             }
 
             if let crate::executor::SupportDatabase::Dameng = options.database {
-                for v in res.data.iter() {
-                    let mut row: Vec<PgColumnItem> = vec![];
-                    for (index, odbc_item) in v.iter().enumerate() {
-                        if let Some(col) = result.columns.get(index) {
-                            row.push((odbc_item, col).try_convert().unwrap());
+                for (row_index, v) in res.data.iter().enumerate() {
+                    let row: Result<Vec<PgColumnItem>, String> = v
+                        .iter()
+                        .zip(conversion.result.columns.iter())
+                        .map(|(odbc_item, col)| (odbc_item, col).try_convert())
+                        .collect();
+                    match (row, mode) {
+                        (Ok(row), _) => conversion.result.data.push(row),
+                        (Err(e), ConversionMode::FailFast) => {
+                            return Err(format!("row {}: {}", row_index, e))
+                        }
+                        (Err(e), ConversionMode::SkipBad) => {
+                            warn!("skip row {}: {}", row_index, e);
+                            conversion.skipped += 1;
+                        }
+                        (Err(e), ConversionMode::Collect) => {
+                            conversion.skipped += 1;
+                            conversion.errors.push(PgRowError {
+                                row_index,
+                                message: e,
+                            });
                         }
                     }
-                    result.data.push(row);
                 }
             }
         }
-        Ok(result)
+        Ok(conversion)
     }
 }
 
@@ -346,6 +425,7 @@ impl TryConvert<Vec<PgColumn>> for (&Vec<OdbcColumn>, &Vec<PgTableItem>, &Option
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::export::fixtures;
     use crate::odbc_api::DataType;
 
     #[test]
@@ -354,14 +434,15 @@ mod tests {
             name: "trace_id".to_string(),
             data_type: DataType::Varchar { length: 255 },
             nullable: true,
+            auto_increment: None,
+            unsigned: None,
+            declared_length: None,
+            comment: None,
         };
 
         let query_result = QueryResult {
             columns: vec![column],
-            data: vec![vec![OdbcColumnItem {
-                odbc_type: OdbcColumnType::Text,
-                value: None,
-            }]],
+            data: vec![vec![fixtures::item(OdbcColumnType::Text, None)]],
         };
 
         let pg_table_item = PgTableItem {
@@ -384,6 +465,18 @@ mod tests {
             max_str_len: 1024,
             max_binary_len: 1024,
             case_sensitive: false,
+            error_for_truncation: false,
+            max_cell_bytes: 0,
+            error_for_oversized_cell: false,
+            coercion: Default::default(),
+            round_floats_to_scale: false,
+            conversion_report: None,
+            tinyint_sign: None,
+            text_buffers: Default::default(),
+            strip_bom: false,
+            collapse_midnight_timestamps: false,
+            hex_binary_text: false,
+            strict_buffer_kind: false,
         };
         let result: PgQueryResult = (query_result, &vec![pg_table_item], &options)
             .try_convert()
@@ -401,4 +494,104 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_query_result_convert_mode() {
+        let column = OdbcColumn::new("age".to_string(), DataType::Integer, true);
+        let item = |v| fixtures::item(OdbcColumnType::I32, Some(v));
+        let query_result = || QueryResult {
+            columns: vec![column.clone()],
+            data: vec![vec![item("1")], vec![item("x")], vec![item("3")]],
+        };
+        let pg_table_item = PgTableItem {
+            name: "AGE".to_string(),
+            table_id: 0,
+            col_index: 0,
+            r#type: PgType::INT4,
+            length: 4,
+            scale: 0,
+            nullable: true,
+            is_identity: false,
+            default_val: None,
+            table_name: "".to_string(),
+            create_time: "".to_string(),
+            subtype: None,
+        };
+        let pg_items = vec![pg_table_item];
+        let options = Options::new(SupportDatabase::Dameng);
+
+        let err = <(QueryResult, &Vec<PgTableItem>, &Options, ConversionMode) as TryConvert<
+            PgConversion,
+        >>::try_convert((
+            query_result(),
+            &pg_items,
+            &options,
+            ConversionMode::FailFast,
+        ))
+        .unwrap_err();
+        assert!(err.starts_with("row 1:"));
+
+        let skip: PgConversion = (query_result(), &pg_items, &options, ConversionMode::SkipBad)
+            .try_convert()
+            .unwrap();
+        assert_eq!(skip.result.data.len(), 2);
+        assert_eq!(skip.skipped, 1);
+        assert!(skip.errors.is_empty());
+
+        let collect: PgConversion = (query_result(), &pg_items, &options, ConversionMode::Collect)
+            .try_convert()
+            .unwrap();
+        assert_eq!(
+            collect.result.data,
+            vec![
+                vec![PgColumnItem::new(Some(PgValueInput::Int4(Some(1))))],
+                vec![PgColumnItem::new(Some(PgValueInput::Int4(Some(3))))],
+            ]
+        );
+        assert_eq!(collect.errors.len(), 1);
+        assert_eq!(collect.errors[0].row_index, 1);
+    }
+
+    #[test]
+    fn test_timestamp_tz_to_utc() {
+        let item = |v| fixtures::item(OdbcColumnType::TimestampTz, Some(v));
+        let utc = |v: &str| NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S%.f").unwrap();
+
+        let converted: PgColumnItem = item("2022-08-24 08:00:00.0-05:30").convert();
+        assert_eq!(
+            converted,
+            PgColumnItem::new(Some(PgValueInput::Timestampz(Some(utc(
+                "2022-08-24 13:30:00.0"
+            )))))
+        );
+        let converted: PgColumnItem = item("2022-08-24 15:50:36.5+08:00").convert();
+        assert_eq!(
+            converted,
+            PgColumnItem::new(Some(PgValueInput::Timestampz(Some(utc(
+                "2022-08-24 07:50:36.5"
+            )))))
+        );
+
+        let column = PgColumn {
+            name: "at".to_string(),
+            pg_type: PgType::TIMESTAMPTZ,
+            oid: PgType::TIMESTAMPTZ.oid(),
+            nullable: true,
+        };
+        let converted: PgColumnItem = (&item("2022-08-24 08:00:00.0-05:30"), &column)
+            .try_convert()
+            .unwrap();
+        assert_eq!(
+            converted,
+            PgColumnItem::new(Some(PgValueInput::Timestampz(Some(utc(
+                "2022-08-24 13:30:00.0"
+            )))))
+        );
+        let err = <(&OdbcColumnItem, &PgColumn) as TryConvert<PgColumnItem>>::try_convert((
+            &item("2022-08-24 08:00:00"),
+            &column,
+        ))
+        .unwrap_err();
+        assert!(err.starts_with("convert column `at`"));
+    }
 }
@@ -0,0 +1,319 @@
+//! Postgres array columns (`int[]`, `text[]`, ...) reach ODBC as their text form, e.g.
+//! `{1,2,NULL}` or `{"a b","c\"d"}`. Cells of such columns are tagged as `OdbcColumnType::Array`
+//! and can be split into typed elements with [`parse_pg_array`].
+use crate::executor::query::QueryResult;
+use crate::extension::odbc::{OdbcColumnItem, OdbcColumnType};
+use crate::extension::pg::PgColumn;
+use bytes::BytesMut;
+use postgres_types::{Kind, Type as PgType};
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// The element type of a Postgres array type, `None` if `pg_type` is not an array.
+pub fn pg_array_element_type(pg_type: &PgType) -> Option<OdbcColumnType> {
+    let member = match pg_type.kind() {
+        Kind::Array(member) => member,
+        _ => return None,
+    };
+    let element_type = match *member {
+        PgType::INT2 => OdbcColumnType::I16,
+        PgType::INT4 => OdbcColumnType::I32,
+        PgType::INT8 => OdbcColumnType::I64,
+        PgType::FLOAT4 => OdbcColumnType::F32,
+        PgType::FLOAT8 => OdbcColumnType::F64,
+        PgType::BOOL => OdbcColumnType::Bit,
+        PgType::BYTEA => OdbcColumnType::Binary,
+        PgType::DATE => OdbcColumnType::Date,
+        PgType::TIME | PgType::TIMETZ => OdbcColumnType::Time,
+        PgType::TIMESTAMP | PgType::TIMESTAMPTZ => OdbcColumnType::Timestamp,
+        _ => OdbcColumnType::Text,
+    };
+    Some(element_type)
+}
+
+/// Tag the text cells of Postgres array columns as `OdbcColumnType::Array`.
+/// `pg_columns` is matched to `result.columns` by position.
+pub fn tag_pg_arrays(result: &mut QueryResult, pg_columns: &[PgColumn]) {
+    let array_columns = pg_columns
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| pg_array_element_type(&c.pg_type).is_some())
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    for row in result.data.iter_mut() {
+        for index in array_columns.iter() {
+            if let Some(item) = row.get_mut(*index) {
+                if let OdbcColumnType::Text | OdbcColumnType::WText = item.odbc_type {
+                    item.odbc_type = OdbcColumnType::Array;
+                }
+            }
+        }
+    }
+}
+
+/// Split the Postgres text form of an array into elements of `element_type`.
+///
+/// Quoted elements may contain `,`, `{`, `}` and backslash escapes, an unquoted `NULL` is a NULL
+/// element while `"NULL"` is the text `NULL`. Nested arrays are returned as `Array` elements
+/// holding their own text form, so they can be parsed again. `bool` elements (`t`/`f`) become
+/// `true`/`false` and `bytea` elements (`\x...`) become raw bytes.
+pub fn parse_pg_array(
+    text: &str,
+    element_type: OdbcColumnType,
+) -> Result<Vec<OdbcColumnItem>, String> {
+    let text = text.trim();
+    // skip dimension decoration, e.g. `[0:2]={1,2,3}`
+    let text = if text.starts_with('[') {
+        match text.split_once('=') {
+            Some((_, t)) => t.trim(),
+            None => return Err(format!("invalid array dimensions:`{}`", text)),
+        }
+    } else {
+        text
+    };
+    let inner = text
+        .strip_prefix('{')
+        .and_then(|t| t.strip_suffix('}'))
+        .ok_or_else(|| format!("invalid array literal:`{}`", text))?;
+
+    let mut items = vec![];
+    if inner.trim().is_empty() {
+        return Ok(items);
+    }
+
+    let mut chars = inner.chars().peekable();
+    loop {
+        skip_whitespace(&mut chars);
+        let item = match chars.peek() {
+            Some('"') => {
+                chars.next();
+                let value = read_quoted(&mut chars)?;
+                parse_element(Some(value), element_type)?
+            }
+            Some('{') => OdbcColumnItem {
+                odbc_type: OdbcColumnType::Array,
+                value: Some(BytesMut::from(read_nested(&mut chars)?.as_bytes())),
+            },
+            _ => {
+                let mut value = String::new();
+                while let Some(c) = chars.next_if(|c| *c != ',') {
+                    value.push(c);
+                }
+                let value = value.trim_end();
+                if value.is_empty() {
+                    return Err(format!("empty element in array:`{}`", text));
+                }
+                if value.eq_ignore_ascii_case("NULL") {
+                    parse_element(None, element_type)?
+                } else {
+                    parse_element(Some(value.to_string()), element_type)?
+                }
+            }
+        };
+        items.push(item);
+
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some(',') => continue,
+            None => break,
+            Some(c) => return Err(format!("unexpected `{}` in array:`{}`", c, text)),
+        }
+    }
+    Ok(items)
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+/// Read a quoted element, the opening quote is already consumed.
+fn read_quoted(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some('\\') => match chars.next() {
+                Some(c) => value.push(c),
+                None => return Err("unterminated escape in array element".to_string()),
+            },
+            Some('"') => return Ok(value),
+            Some(c) => value.push(c),
+            None => return Err("unterminated quoted array element".to_string()),
+        }
+    }
+}
+
+/// Read a nested array verbatim, including its braces.
+fn read_nested(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    let mut value = String::new();
+    let mut depth = 0;
+    let mut in_quotes = false;
+    while let Some(c) = chars.next() {
+        value.push(c);
+        match c {
+            '\\' if in_quotes => {
+                if let Some(escaped) = chars.next() {
+                    value.push(escaped);
+                }
+            }
+            '"' => in_quotes = !in_quotes,
+            '{' if !in_quotes => depth += 1,
+            '}' if !in_quotes => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(value);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(format!("unterminated nested array:`{}`", value))
+}
+
+fn parse_element(
+    value: Option<String>,
+    element_type: OdbcColumnType,
+) -> Result<OdbcColumnItem, String> {
+    let invalid = |v: &str| format!("invalid {:?} array element:`{}`", element_type, v);
+    let value = match value {
+        None => None,
+        Some(v) => {
+            let bytes = match element_type {
+                OdbcColumnType::Bit => match v.as_str() {
+                    "t" | "true" => BytesMut::from("true"),
+                    "f" | "false" => BytesMut::from("false"),
+                    _ => return Err(invalid(&v)),
+                },
+                OdbcColumnType::Binary => {
+                    BytesMut::from(decode_bytea(&v).ok_or_else(|| invalid(&v))?.as_slice())
+                }
+                OdbcColumnType::U8 => {
+                    let b = v.parse::<u8>().map_err(|_| invalid(&v))?;
+                    BytesMut::from(&[b][..])
+                }
+                OdbcColumnType::I8
+                | OdbcColumnType::I16
+                | OdbcColumnType::I32
+                | OdbcColumnType::I64 => {
+                    v.parse::<i64>().map_err(|_| invalid(&v))?;
+                    BytesMut::from(v.as_bytes())
+                }
+                OdbcColumnType::U64 => {
+                    v.parse::<u64>().map_err(|_| invalid(&v))?;
+                    BytesMut::from(v.as_bytes())
+                }
+                OdbcColumnType::F32 | OdbcColumnType::F64 => {
+                    v.parse::<f64>().map_err(|_| invalid(&v))?;
+                    BytesMut::from(v.as_bytes())
+                }
+                _ => BytesMut::from(v.as_bytes()),
+            };
+            Some(bytes)
+        }
+    };
+    Ok(OdbcColumnItem {
+        odbc_type: element_type,
+        value,
+    })
+}
+
+/// Decode the `bytea` hex output format, e.g. `\x01ff`.
+fn decode_bytea(v: &str) -> Option<Vec<u8>> {
+    let hex = v.strip_prefix("\\x")?;
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(items: &[OdbcColumnItem]) -> Vec<Option<String>> {
+        items.iter().map(|i| i.value_to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_int_array() {
+        let items = parse_pg_array("{1, 2,NULL,-3}", OdbcColumnType::I32).unwrap();
+        assert!(items.iter().all(|i| i.odbc_type == OdbcColumnType::I32));
+        assert_eq!(
+            texts(&items),
+            vec![Some("1".into()), Some("2".into()), None, Some("-3".into())]
+        );
+        assert!(parse_pg_array("{1,x}", OdbcColumnType::I32).is_err());
+        assert!(parse_pg_array("{}", OdbcColumnType::I32)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_quoted_array() {
+        let items = parse_pg_array(
+            r#"{plain,"a,b","say \"hi\"","back\\slash","NULL",NULL,"{x}"}"#,
+            OdbcColumnType::Text,
+        )
+        .unwrap();
+        assert_eq!(
+            texts(&items),
+            vec![
+                Some("plain".into()),
+                Some("a,b".into()),
+                Some("say \"hi\"".into()),
+                Some("back\\slash".into()),
+                Some("NULL".into()),
+                None,
+                Some("{x}".into()),
+            ]
+        );
+        assert!(parse_pg_array(r#"{"open}"#, OdbcColumnType::Text).is_err());
+    }
+
+    #[test]
+    fn test_parse_nested_array() {
+        let items = parse_pg_array(r#"{{1,2},{3,NULL}}"#, OdbcColumnType::I64).unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|i| i.odbc_type == OdbcColumnType::Array));
+        assert_eq!(
+            texts(&items),
+            vec![Some("{1,2}".into()), Some("{3,NULL}".into())]
+        );
+
+        let inner = parse_pg_array(&items[1].value_to_string().unwrap(), OdbcColumnType::I64);
+        assert_eq!(texts(&inner.unwrap()), vec![Some("3".into()), None]);
+
+        let items = parse_pg_array(r#"[0:1]={{"a}",b},{c,d}}"#, OdbcColumnType::Text).unwrap();
+        assert_eq!(
+            texts(&items),
+            vec![Some(r#"{"a}",b}"#.into()), Some("{c,d}".into())]
+        );
+    }
+
+    #[test]
+    fn test_parse_bool_and_bytea_array() {
+        let items = parse_pg_array("{t,f,NULL}", OdbcColumnType::Bit).unwrap();
+        assert_eq!(
+            texts(&items),
+            vec![Some("true".into()), Some("false".into()), None]
+        );
+
+        let items = parse_pg_array(r#"{"\\x01ff"}"#, OdbcColumnType::Binary).unwrap();
+        assert_eq!(items[0].value.as_deref(), Some(&[0x01u8, 0xff][..]));
+    }
+
+    #[test]
+    fn test_pg_array_element_type() {
+        assert_eq!(
+            pg_array_element_type(&PgType::INT4_ARRAY),
+            Some(OdbcColumnType::I32)
+        );
+        assert_eq!(
+            pg_array_element_type(&PgType::TEXT_ARRAY),
+            Some(OdbcColumnType::Text)
+        );
+        assert_eq!(pg_array_element_type(&PgType::INT4), None);
+    }
+}
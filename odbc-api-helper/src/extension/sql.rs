@@ -0,0 +1,235 @@
+//! Render identifiers and values as SQL text, for logging, debugging and generated scripts.
+//! The output is meant to be read or replayed by tooling, real statements should bind parameters.
+use crate::error::OdbcHelperError;
+use crate::executor::SupportDatabase;
+use crate::extension::odbc::{OdbcColumn, OdbcColumnItem, OdbcColumnType};
+
+/// Quoting and escaping rules of the target database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    /// `"x"` identifiers, `'it''s'` strings, `X'0102'` binary. Postgres and Dameng, default.
+    #[default]
+    Standard,
+    /// `` `x` `` identifiers, strings additionally escape backslashes: `'a\\b'`.
+    Mysql,
+    /// `[x]` identifiers, `N'...'` wide strings, `0x0102` binary and `1`/`0` bits.
+    SqlServer,
+}
+
+impl From<&SupportDatabase> for Dialect {
+    fn from(database: &SupportDatabase) -> Self {
+        match database {
+            SupportDatabase::Mysql => Dialect::Mysql,
+            SupportDatabase::Dameng | SupportDatabase::Pg => Dialect::Standard,
+        }
+    }
+}
+
+/// `name` quoted as an identifier, embedded closing quotes are doubled.
+pub fn quote_identifier(name: &str, dialect: Dialect) -> String {
+    match dialect {
+        Dialect::Standard => format!("\"{}\"", name.replace('"', "\"\"")),
+        Dialect::Mysql => format!("`{}`", name.replace('`', "``")),
+        Dialect::SqlServer => format!("[{}]", name.replace(']', "]]")),
+    }
+}
+
+/// `item` as a SQL literal, e.g. `NULL`, `42`, `'it''s'` or `DATE '2022-01-02'`.
+///
+/// Non-finite floats are quoted (`'NaN'`), `Json`, `Array`, `TimestampTz` and `Interval` are
+/// plain string literals of the driver's text.
+pub fn to_sql_literal(item: &OdbcColumnItem, dialect: Dialect) -> String {
+    let text = match item.value_to_string() {
+        Some(text) => text,
+        None => return "NULL".to_string(),
+    };
+    match item.odbc_type {
+        OdbcColumnType::I8
+        | OdbcColumnType::I16
+        | OdbcColumnType::I32
+        | OdbcColumnType::I64
+        | OdbcColumnType::U8
+        | OdbcColumnType::U64 => text,
+        OdbcColumnType::F32 | OdbcColumnType::F64 => {
+            if text.parse::<f64>().is_ok_and(|v| v.is_finite()) {
+                text
+            } else {
+                string_literal(&text, dialect)
+            }
+        }
+        OdbcColumnType::Bit => match (dialect, text.as_str()) {
+            (Dialect::SqlServer, "true") => "1".to_string(),
+            (Dialect::SqlServer, _) => "0".to_string(),
+            (_, "true") => "TRUE".to_string(),
+            _ => "FALSE".to_string(),
+        },
+        OdbcColumnType::Binary => match dialect {
+            Dialect::SqlServer => text,
+            _ => format!("X'{}'", &text[2..]),
+        },
+        OdbcColumnType::Date | OdbcColumnType::Time | OdbcColumnType::Timestamp
+            if dialect != Dialect::SqlServer =>
+        {
+            let keyword = match item.odbc_type {
+                OdbcColumnType::Date => "DATE",
+                OdbcColumnType::Time => "TIME",
+                _ => "TIMESTAMP",
+            };
+            format!("{} {}", keyword, string_literal(&text, dialect))
+        }
+        OdbcColumnType::WText if dialect == Dialect::SqlServer => {
+            format!("N{}", string_literal(&text, dialect))
+        }
+        _ => string_literal(&text, dialect),
+    }
+}
+
+/// Multi-row `INSERT INTO table (...) VALUES (...), (...)` statements, `batch_size` rows each
+/// (at least 1, SQL Server accepts up to 1000), for copying small data sets between
+/// environments. `table` may be qualified, e.g. `dbo.users`, each part is quoted.
+/// A row without exactly one value per column is rejected with
+/// `OdbcHelperError::RowValidationError`, no statements are returned then.
+///
+/// Not injection safe: the statements embed the values as literals, never run them with data
+/// from untrusted sources.
+pub fn to_insert_statements(
+    table: &str,
+    columns: &[OdbcColumn],
+    rows: &[Vec<OdbcColumnItem>],
+    dialect: Dialect,
+    batch_size: usize,
+) -> Result<Vec<String>, OdbcHelperError> {
+    if let Some((index, row)) = rows
+        .iter()
+        .enumerate()
+        .find(|(_, row)| row.len() != columns.len())
+    {
+        return Err(OdbcHelperError::RowValidationError(format!(
+            "row {} has {} values for {} columns",
+            index + 1,
+            row.len(),
+            columns.len()
+        )));
+    }
+    let table = table
+        .split('.')
+        .map(|part| quote_identifier(part, dialect))
+        .collect::<Vec<_>>()
+        .join(".");
+    let names = columns
+        .iter()
+        .map(|c| quote_identifier(&c.name, dialect))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let statements = rows
+        .chunks(batch_size.max(1))
+        .map(|chunk| {
+            let values = chunk
+                .iter()
+                .map(|row| {
+                    let literals = row
+                        .iter()
+                        .map(|item| to_sql_literal(item, dialect))
+                        .collect::<Vec<_>>();
+                    format!("({})", literals.join(", "))
+                })
+                .collect::<Vec<_>>();
+            format!(
+                "INSERT INTO {} ({}) VALUES {};",
+                table,
+                names,
+                values.join(", ")
+            )
+        })
+        .collect();
+    Ok(statements)
+}
+
+fn string_literal(text: &str, dialect: Dialect) -> String {
+    let text = match dialect {
+        Dialect::Mysql => text.replace('\\', "\\\\"),
+        Dialect::Standard | Dialect::SqlServer => text.to_string(),
+    };
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::fixtures;
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("a\"b", Dialect::Standard), "\"a\"\"b\"");
+        assert_eq!(quote_identifier("a`b", Dialect::Mysql), "`a``b`");
+        assert_eq!(quote_identifier("a]b", Dialect::SqlServer), "[a]]b]");
+    }
+
+    #[test]
+    fn test_to_sql_literal() {
+        let item = |odbc_type, v| fixtures::bytes_item(odbc_type, v);
+        let text = item(OdbcColumnType::Text, Some(br"it's C:\tmp"));
+        assert_eq!(to_sql_literal(&text, Dialect::Standard), r"'it''s C:\tmp'");
+        assert_eq!(to_sql_literal(&text, Dialect::Mysql), r"'it''s C:\\tmp'");
+
+        let binary = item(OdbcColumnType::Binary, Some(&[0x01, 0xab]));
+        assert_eq!(to_sql_literal(&binary, Dialect::Standard), "X'01AB'");
+        assert_eq!(to_sql_literal(&binary, Dialect::SqlServer), "0x01AB");
+
+        let date = item(OdbcColumnType::Date, Some(b"2022-01-02"));
+        assert_eq!(to_sql_literal(&date, Dialect::Mysql), "DATE '2022-01-02'");
+        assert_eq!(to_sql_literal(&date, Dialect::SqlServer), "'2022-01-02'");
+
+        let bit = item(OdbcColumnType::Bit, Some(b"true"));
+        assert_eq!(to_sql_literal(&bit, Dialect::Standard), "TRUE");
+        assert_eq!(to_sql_literal(&bit, Dialect::SqlServer), "1");
+
+        let nan = item(OdbcColumnType::F64, Some(b"NaN"));
+        assert_eq!(to_sql_literal(&nan, Dialect::Standard), "'NaN'");
+        assert_eq!(
+            to_sql_literal(&item(OdbcColumnType::I32, None), Dialect::Standard),
+            "NULL"
+        );
+    }
+
+    #[test]
+    fn test_to_insert_statements() {
+        use crate::odbc_api::DataType;
+
+        let columns = vec![
+            OdbcColumn::new("id".to_string(), DataType::Integer, false),
+            OdbcColumn::new("name".to_string(), DataType::Varchar { length: 20 }, true),
+        ];
+        let row = |id: &str, name: Option<&str>| {
+            vec![
+                fixtures::item(OdbcColumnType::I32, Some(id)),
+                fixtures::item(OdbcColumnType::Text, name),
+            ]
+        };
+        let rows = vec![
+            row("1", Some("o'neil")),
+            row("2", None),
+            row("3", Some("c")),
+        ];
+        let statements =
+            to_insert_statements("dbo.users", &columns, &rows, Dialect::SqlServer, 2).unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                "INSERT INTO [dbo].[users] ([id], [name]) VALUES (1, 'o''neil'), (2, NULL);",
+                "INSERT INTO [dbo].[users] ([id], [name]) VALUES (3, 'c');",
+            ]
+        );
+        assert!(to_insert_statements("t", &columns, &[], Dialect::Mysql, 10)
+            .unwrap()
+            .is_empty());
+
+        let mut rows = rows;
+        rows[1].pop();
+        let err = to_insert_statements("t", &columns, &rows, Dialect::Mysql, 10).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid row:row 2 has 1 values for 2 columns"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Long text fetched in pieces (`SQLGetData`) arrives as byte chunks that may split a multibyte
+//! UTF-8 sequence. [`TextAssembler`] keeps the incomplete tail of a chunk until the next one.
+use crate::error::OdbcHelperError;
+use crate::extension::odbc::{OdbcColumnItem, OdbcColumnType};
+use bytes::BytesMut;
+
+/// How invalid UTF-8 is handled by [`TextAssembler`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextDecoding {
+    /// Replace invalid sequences with `U+FFFD`, default.
+    #[default]
+    Lossy,
+    /// Fail with `OdbcHelperError::TypeConversionError`.
+    Strict,
+}
+
+/// Assemble successive chunks of one text value into an `OdbcColumnType::Text` item.
+#[derive(Debug, Default)]
+pub struct TextAssembler {
+    decoding: TextDecoding,
+    text: String,
+    // incomplete UTF-8 sequence at the end of the last chunk, at most 3 bytes
+    pending: Vec<u8>,
+}
+
+impl TextAssembler {
+    pub fn new(decoding: TextDecoding) -> Self {
+        TextAssembler {
+            decoding,
+            ..Default::default()
+        }
+    }
+
+    /// Append the next chunk, a sequence cut at the end is completed by the following chunk.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), OdbcHelperError> {
+        self.pending.extend_from_slice(chunk);
+        let mut input = std::mem::take(&mut self.pending);
+        let mut start = 0;
+        loop {
+            match std::str::from_utf8(&input[start..]) {
+                Ok(s) => {
+                    self.text.push_str(s);
+                    break;
+                }
+                Err(e) => {
+                    let valid = start + e.valid_up_to();
+                    if let Ok(s) = std::str::from_utf8(&input[start..valid]) {
+                        self.text.push_str(s);
+                    }
+                    match e.error_len() {
+                        // incomplete sequence at the end, wait for the next chunk
+                        None => {
+                            input.drain(..valid);
+                            self.pending = input;
+                            break;
+                        }
+                        Some(len) => {
+                            self.invalid(&input[valid..valid + len])?;
+                            start = valid + len;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The assembled text, an incomplete sequence left at the end is invalid.
+    pub fn finish(mut self) -> Result<OdbcColumnItem, OdbcHelperError> {
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            self.invalid(&pending)?;
+        }
+        Ok(OdbcColumnItem {
+            odbc_type: OdbcColumnType::Text,
+            value: Some(BytesMut::from(self.text.as_bytes())),
+        })
+    }
+
+    fn invalid(&mut self, bytes: &[u8]) -> Result<(), OdbcHelperError> {
+        match self.decoding {
+            TextDecoding::Lossy => {
+                self.text.push(char::REPLACEMENT_CHARACTER);
+                Ok(())
+            }
+            TextDecoding::Strict => Err(OdbcHelperError::TypeConversionError(format!(
+                "Text, invalid utf-8 {:02x?} after `{}` bytes",
+                bytes,
+                self.text.len()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_multibyte_sequence() {
+        let text = "a€b中";
+        let bytes = text.as_bytes();
+        // `€` is 3 bytes at 1..4, split it after its first byte
+        let mut assembler = TextAssembler::new(TextDecoding::Strict);
+        for chunk in [&bytes[..2], &bytes[2..3], &bytes[3..6], &bytes[6..]] {
+            assembler.push(chunk).unwrap();
+        }
+        let item = assembler.finish().unwrap();
+        assert_eq!(item.odbc_type, OdbcColumnType::Text);
+        assert_eq!(item.value_to_string().as_deref(), Some(text));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let mut assembler = TextAssembler::new(TextDecoding::Lossy);
+        assembler.push(b"a\xffb\xe2\x82").unwrap();
+        let item = assembler.finish().unwrap();
+        assert_eq!(
+            item.value_to_string().as_deref(),
+            Some("a\u{FFFD}b\u{FFFD}")
+        );
+
+        let mut assembler = TextAssembler::new(TextDecoding::Strict);
+        assert!(assembler.push(b"a\xffb").is_err());
+
+        let mut assembler = TextAssembler::new(TextDecoding::Strict);
+        assembler.push(b"ok\xe2\x82").unwrap();
+        assert!(assembler.finish().is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::extension::odbc::parse_timestamp_tz;
 use bytes::BytesMut;
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 
@@ -62,11 +63,23 @@ pub fn parse_to_time(v: BytesMut) -> anyhow::Result<NaiveTime> {
         } else {
             "%H:%M:%S%.f"
         },
-    )
-    .unwrap();
+    )?;
     Ok(time)
 }
 
+/// Parse a timestamp with an offset, e.g. `2022-08-24 08:00:00.0-05:30` (see
+/// [`parse_timestamp_tz`]), into the instant as UTC.
+pub fn parse_to_utc_date_time(v: BytesMut) -> anyhow::Result<NaiveDateTime> {
+    let val = String::from_utf8_lossy(v.as_ref()).to_string();
+    let utc = parse_timestamp_tz(&val)?.to_offset(time::UtcOffset::UTC);
+    NaiveDate::from_ymd_opt(utc.year(), utc.month() as u32, utc.day() as u32)
+        .and_then(|date| {
+            let (hour, minute, second, nano) = utc.to_hms_nano();
+            date.and_hms_nano_opt(hour as u32, minute as u32, second as u32, nano)
+        })
+        .ok_or_else(|| anyhow::anyhow!("timestamp `{}` is out of range", val))
+}
+
 pub fn parse_to_data_time(v: BytesMut) -> anyhow::Result<NaiveDateTime> {
     let val = String::from_utf8_lossy(v.as_ref()).to_string();
     let date_time = NaiveDateTime::parse_from_str(
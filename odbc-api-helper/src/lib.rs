@@ -14,8 +14,10 @@ pub(crate) use odbc_common::odbc_api;
 pub mod bridge;
 pub mod error;
 pub mod executor;
+pub mod export;
 pub mod extension;
 
+pub use extension::nu::to_nu_table;
 pub use odbc_common::Print;
 
 pub trait Convert<T>: Sized {
@@ -26,3 +28,15 @@ pub trait TryConvert<T>: Sized {
     type Error;
     fn try_convert(self) -> Result<T, Self::Error>;
 }
+
+/// How a batch conversion reacts to a row that fails to convert.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConversionMode {
+    /// Return the first error, default.
+    #[default]
+    FailFast,
+    /// Drop the offending rows and report how many were skipped.
+    SkipBad,
+    /// Keep all good rows and return every row error alongside them.
+    Collect,
+}
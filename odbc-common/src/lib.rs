@@ -4,7 +4,9 @@ extern crate log;
 pub extern crate odbc_api;
 
 pub mod print_table;
+pub mod value;
 pub use nu_protocol::*;
 pub use nu_table::*;
 
 pub use print_table::Print;
+pub use value::SqlValue;
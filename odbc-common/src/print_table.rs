@@ -5,6 +5,11 @@ use nu_table::{Alignments, StyledString, TableTheme, TextStyle};
 use odbc_api::buffers::TextRowSet;
 use odbc_api::Cursor;
 use std::collections::HashMap;
+use std::io::IsTerminal;
+use unicode_width::UnicodeWidthStr;
+
+/// Table width used when stdout is not a terminal, e.g. redirected to a file or in CI.
+pub const NON_TTY_TABLE_WIDTH: usize = 120;
 
 pub trait Print: Sized {
     fn print_all_tables(self) -> anyhow::Result<()> {
@@ -16,16 +21,117 @@ pub trait Print: Sized {
     fn convert_table(self) -> anyhow::Result<Table>;
 
     fn table_string(self) -> anyhow::Result<String> {
-        let table = self.convert_table()?;
-        let cfg = Config::default();
-        let styles = HashMap::default();
-        let alignments = Alignments::default();
-
-        let p = table
-            .draw_table(&cfg, &styles, alignments, usize::MAX)
-            .ok_or_else(|| anyhow!("convert table to string error"))?;
-        Ok(p)
+        draw_table(self.convert_table()?)
+    }
+}
+
+/// Draw a nu_table `Table` with the default config, cells are never wrapped.
+pub fn draw_table(table: Table) -> anyhow::Result<String> {
+    draw_table_with_width(table, usize::MAX)
+}
+
+/// Width available for a table: `width` when set, otherwise `COLUMNS` when stdout is a terminal
+/// (unbounded if unset) or [`NON_TTY_TABLE_WIDTH`] when it is redirected.
+pub fn table_width(width: Option<usize>) -> usize {
+    if let Some(width) = width {
+        return width;
+    }
+    if std::io::stdout().is_terminal() {
+        std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .unwrap_or(usize::MAX)
+    } else {
+        NON_TTY_TABLE_WIDTH
+    }
+}
+
+/// Draw a nu_table `Table` fitting `width` columns, wider cells are wrapped.
+pub fn draw_table_with_width(table: Table, width: usize) -> anyhow::Result<String> {
+    let cfg = Config::default();
+    let styles = HashMap::default();
+    let alignments = Alignments::default();
+
+    let p = table
+        .draw_table(&cfg, &styles, alignments, width)
+        .ok_or_else(|| anyhow!("convert table to string error"))?;
+    Ok(p)
+}
+
+/// Render a table with fixed column padding computed in a single pass over the cells.
+/// Empty `headers` render the rows only.
+///
+/// Unlike the nu_table renderer it never wraps or truncates cells, which keeps it fast and
+/// readable for very wide or large result sets on constrained terminals. E.g:
+/// ```text
+/// id | name
+/// ---+------
+/// 1  | hallo
+/// ```
+pub fn simple_table_string(headers: &[String], rows: &[Vec<String>]) -> String {
+    let widths = cell_widths(headers, rows);
+    if headers.is_empty() {
+        return rows
+            .iter()
+            .map(|row| format_table_row(row, &widths))
+            .collect::<Vec<_>>()
+            .join("\n");
     }
+    let mut table = format_table_row(headers, &widths);
+    table.push('\n');
+    table.push_str(&table_separator(&widths));
+    for row in rows {
+        table.push('\n');
+        table.push_str(&format_table_row(row, &widths));
+    }
+    table
+}
+
+/// Terminal columns taken by `text`: wide characters (e.g. CJK) count as 2 and combining marks
+/// as 0, so tables with non-ASCII text stay aligned.
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Column widths of [`simple_table_string`], the widest header or cell of every column by
+/// [`display_width`].
+pub fn cell_widths(headers: &[String], rows: &[Vec<String>]) -> Vec<usize> {
+    let mut widths: Vec<usize> = headers.iter().map(|h| display_width(h)).collect();
+    for row in rows {
+        for (index, cell) in row.iter().enumerate() {
+            let width = display_width(cell);
+            match widths.get_mut(index) {
+                Some(w) => *w = (*w).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+    widths
+}
+
+/// A line of [`simple_table_string`], cells left aligned in `widths`. A cell wider than its
+/// column is written in full and shifts the rest of the line.
+pub fn format_table_row(cells: &[String], widths: &[usize]) -> String {
+    let line = widths
+        .iter()
+        .enumerate()
+        .map(|(index, width)| {
+            let cell = cells.get(index).map(|c| c.as_str()).unwrap_or_default();
+            let padding = width.saturating_sub(display_width(cell));
+            format!("{}{}", cell, " ".repeat(padding))
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+    line.trim_end().to_string()
+}
+
+/// The `---+---` line of [`simple_table_string`] below the headers.
+pub fn table_separator(widths: &[usize]) -> String {
+    widths
+        .iter()
+        .map(|w| "-".repeat(*w))
+        .collect::<Vec<_>>()
+        .join("-+-")
 }
 
 const BATCH_SIZE: usize = 128;
@@ -65,7 +171,6 @@ where
                 // Within a row iterate over every column
                 let row_data = (0..batch.num_cols())
                     .map(|col_index| batch.at(col_index, row_index).unwrap_or(&[]))
-                    .into_iter()
                     .map(|x| String::from_utf8_lossy(x).to_string())
                     .map(|x| StyledString::new(x, TextStyle::basic_left()))
                     .collect();
@@ -77,3 +182,48 @@ where
         Ok(table)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_table_string() {
+        let headers = vec!["id".to_string(), "name".to_string()];
+        let rows = vec![
+            vec!["1".to_string(), "hallo".to_string()],
+            vec!["100".to_string(), "".to_string()],
+        ];
+        assert_eq!(
+            simple_table_string(&headers, &rows),
+            "id  | name\n----+------\n1   | hallo\n100 |"
+        );
+        assert_eq!(simple_table_string(&[], &rows), "1   | hallo\n100 |");
+
+        let rows = vec![
+            vec!["\u{4e16}\u{754c}".to_string(), "x".to_string()],
+            vec!["abcd".to_string(), "y".to_string()],
+        ];
+        assert_eq!(
+            simple_table_string(&[], &rows),
+            "\u{4e16}\u{754c} | x\nabcd | y"
+        );
+    }
+
+    #[test]
+    fn test_draw_table_with_width() {
+        assert_eq!(table_width(Some(80)), 80);
+
+        let headers = vec![StyledString::new(
+            "note".to_string(),
+            TextStyle::default_header(),
+        )];
+        let rows = vec![vec![StyledString::new(
+            "word ".repeat(20),
+            TextStyle::basic_left(),
+        )]];
+        let table = Table::new(headers, rows, TableTheme::rounded());
+        let drawn = draw_table_with_width(table, 40).unwrap();
+        assert!(drawn.lines().all(|line| line.chars().count() <= 40));
+    }
+}
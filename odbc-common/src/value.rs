@@ -0,0 +1,27 @@
+//! Backend-neutral SQL value, for code abstracting over several database drivers.
+
+/// A single SQL value independent of the driver's buffer types.
+///
+/// Integers of every width share `Int` and floats share `Float`, so converting into `SqlValue`
+/// can lose the exact source type, e.g. a `SMALLINT` and a `BIGINT` both become `Int`.
+/// Temporal values keep the text form of the driver, e.g. `2022-08-24 15:50:36.5`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+    Date(String),
+    Time(String),
+    /// Timestamp with or without a timezone offset.
+    Timestamp(String),
+    Json(String),
+}
+
+impl SqlValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, SqlValue::Null)
+    }
+}